@@ -0,0 +1,148 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+#![allow(nonstandard_style)]
+
+extern crate proc_macro;
+
+use std::env;
+use std::path::PathBuf;
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+use proc_parser::analysis::*;
+use proc_parser::build::Loader;
+use proc_parser::codegen::Generator;
+
+// Expands a grammar written in .ppg notation to its AST types and parser implementations,
+// the same code proc_parser::codegen::Generator emits:
+//
+//     ppg! {
+//         Program := ($stmts:Stmt, SEMICOLON, EOF);
+//         Stmt := $name:Ident, DEFINE, $value:Ident;
+//     }
+//
+// Rust does not keep newlines, so definitions and branch patterns are separated by `;` explicitly.
+// Imports are relative to the directory of the crate manifest.
+// Grammar errors are reported at the tokens of the input they refer to.
+#[proc_macro]
+pub fn ppg(input: TokenStream) -> TokenStream {
+    let mut source = Source { Text: String::new(), Chars: 0, Spans: vec![] };
+    source.Tokens(input);
+
+    let dir = env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from).unwrap_or_default();
+    let mut loader = Loader::new();
+    let file = match loader.LoadSource("ppg!", &dir, &source.Text) {
+        Ok(file) => file,
+        Err(diagnostics) => return source.Errors(&loader.Paths, &diagnostics),
+    };
+
+    let diagnostics = Check(&file);
+    if diagnostics.iter().any(Diagnostic::IsError) {
+        return source.Errors(&loader.Paths, &diagnostics);
+    }
+
+    match Generator::new().Generate(&file) {
+        Ok(code) => code.parse().expect("generated code is valid Rust"),
+        Err(e) => source.Errors(&loader.Paths, &e.Diagnostics()),
+    }
+}
+
+// Grammar source rebuilt from the macro input.
+struct Source {
+    Text: String,
+    // Length of Text in chars, the unit of Position.Offset.
+    Chars: usize,
+    // Offset at which each input token starts, with its span.
+    Spans: Vec<(usize, Span)>,
+}
+
+impl Source {
+    fn Write(&mut self, text: &str, span: Span) {
+        self.Spans.push((self.Chars, span));
+        self.Text.push_str(text);
+        self.Chars += text.chars().count();
+    }
+
+    fn Space(&mut self) {
+        self.Text.push(' ');
+        self.Chars += 1;
+    }
+
+    fn Tokens(&mut self, input: TokenStream) {
+        for tree in input {
+            match tree {
+                TokenTree::Group(group) => {
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::None => ("", ""),
+                    };
+                    self.Write(open, group.span_open());
+                    self.Space();
+                    self.Tokens(group.stream());
+                    self.Write(close, group.span_close());
+                    self.Space();
+                }
+                TokenTree::Punct(punct) => {
+                    // `:=` and `=>` arrive as joint punctuation.
+                    self.Write(&punct.as_char().to_string(), punct.span());
+                    if punct.spacing() == Spacing::Alone {
+                        self.Space();
+                    }
+                }
+                TokenTree::Ident(ident) => {
+                    self.Write(&ident.to_string(), ident.span());
+                    self.Space();
+                }
+                TokenTree::Literal(literal) => {
+                    self.Write(&literal.to_string(), literal.span());
+                    self.Space();
+                }
+            }
+        }
+    }
+
+    // Span of the input token covering the offset.
+    fn Span(&self, offset: usize) -> Span {
+        self.Spans.iter().rev()
+            .find(|(begin, _)| *begin <= offset)
+            .map_or_else(Span::call_site, |(_, span)| *span)
+    }
+
+    // Errors located in imported files are reported at the macro call with their file.
+    fn Errors(&self, paths: &[String], diagnostics: &[Diagnostic]) -> TokenStream {
+        diagnostics.iter()
+            .filter(|diagnostic| diagnostic.IsError())
+            .flat_map(|diagnostic| match diagnostic.Pos {
+                Some(pos) if pos.Begin.Source == 0 => CompileError(&diagnostic.Message, self.Span(pos.Begin.Offset)),
+                Some(_) => CompileError(&diagnostic.FormatIn(paths), Span::call_site()),
+                None => CompileError(&diagnostic.Message, Span::call_site()),
+            })
+            .collect()
+    }
+}
+
+// compile_error!("message");
+fn CompileError(message: &str, span: Span) -> TokenStream {
+    let mut literal = Literal::string(message);
+    literal.set_span(span);
+
+    let mut bang = Punct::new('!', Spacing::Alone);
+    bang.set_span(span);
+
+    let mut group = Group::new(Delimiter::Parenthesis, TokenStream::from(TokenTree::Literal(literal)));
+    group.set_span(span);
+
+    let mut semicolon = Punct::new(';', Spacing::Alone);
+    semicolon.set_span(span);
+
+    TokenStream::from_iter([
+        TokenTree::Ident(Ident::new("compile_error", span)),
+        TokenTree::Punct(bang),
+        TokenTree::Group(group),
+        TokenTree::Punct(semicolon),
+    ])
+}
@@ -0,0 +1,42 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+#![allow(nonstandard_style)]
+
+use proc_parser::parser::{AstNodeParserTrait, Parser};
+
+mod grammar {
+    proc_parser_macros::ppg! {
+        Program := ($stmts:Stmt, SEMICOLON, EOF);
+        Stmt := $name:Ident, DEFINE, $value:Value;
+        Value := {
+            Ident => $name:Ident;
+            Int => $int:Int;
+            LBRACK => LBRACK, ($items:Value, COMMA, RBRACK), RBRACK;
+        }
+    }
+}
+
+#[test]
+fn TestPpg_Parse() {
+    let mut p = Parser::new("a := b\nc := [1, d]\n".chars().collect());
+    p.Scan().unwrap();
+
+    let program = grammar::Program::Expect(&mut p).unwrap();
+
+    let stmts = &program.Stmts.Elements;
+    assert_eq!(stmts.len(), 2);
+    assert_eq!(stmts[0].Name.Literal, "a");
+    assert!(matches!(&stmts[0].Value, grammar::Value::Name(name) if name.Literal == "b"));
+    match &stmts[1].Value {
+        grammar::Value::Items(items) => assert_eq!(items.Elements.len(), 2),
+        _ => panic!()
+    }
+}
+
+// Errors point at the tokens of the macro input they are about, see tests/ui.
+#[test]
+fn TestPpg_Errors() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
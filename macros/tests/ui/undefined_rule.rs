@@ -0,0 +1,12 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+mod grammar {
+    proc_parser_macros::ppg! {
+        Program := ($stmts:Stmt, SEMICOLON, EOF);
+        Stmt := $name:Ident, DEFINE, $value:Missing;
+    }
+}
+
+fn main() {}
@@ -0,0 +1,609 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::fs;
+
+use crate::analysis::*;
+use crate::ast::{Annotation, File, TokenKind};
+use crate::parser::{AstNodeParserTrait, Parser};
+
+fn parse(src: &str) -> File {
+    let mut p = File::NewParser(src);
+    p.Scan().unwrap();
+    File::Expect(&mut p).unwrap()
+}
+
+#[test]
+fn TestResolve_MetaGrammar() {
+    let file = parse(&fs::read_to_string("proc-parser.ppg").unwrap());
+
+    let symbols = Symbols::Resolve(&file).unwrap();
+    assert!(matches!(symbols.Lookup("Node"), Some(Symbol::Rule(_))));
+    assert!(matches!(symbols.Lookup("SEMICOLON"), Some(Symbol::Token(_))));
+    assert!(matches!(symbols.Lookup("_"), Some(Symbol::Any)));
+}
+
+#[test]
+fn TestResolve_Errors() {
+    let file = parse(r#"
+A := $x:Ident, $x:B, COMMA
+A := { Ident => $y:Ident; COMMA => $y:Ident; }, $y:Ident
+B := ($items:Missing, COMMA, TERM)
+"#);
+
+    let errors = Symbols::Resolve(&file).err().unwrap();
+    for e in &errors {
+        println!("{:?}", e);
+    }
+
+    let undefined: Vec<&str> = errors.iter().filter_map(|e| match e {
+        ResolveError::UndefinedName(e) => Some(e.Name.Literal.as_str()),
+        _ => None
+    }).collect();
+    assert_eq!(undefined, vec!["Missing", "TERM"]);
+
+    assert_eq!(errors.iter().filter(|e| matches!(e, ResolveError::DuplicateDefinition(_))).count(), 1);
+
+    // The two branch arms may share `y`, the field after the branch may not.
+    let fields: Vec<&str> = errors.iter().filter_map(|e| match e {
+        ResolveError::DuplicateField(e) => Some(e.Name.Literal.as_str()),
+        _ => None
+    }).collect();
+    assert_eq!(fields, vec!["x", "y"]);
+}
+
+fn set(names: &[&str]) -> TokenSet {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn TestGrammarSets_MetaGrammar() {
+    let file = parse(&fs::read_to_string("proc-parser.ppg").unwrap());
+    let symbols = Symbols::Resolve(&file).unwrap();
+
+    let sets = GrammarSets::Compute(&file, &symbols);
+
+    assert_eq!(sets.FirstOf("Node"), Some(&set(&["AND", "FIELD", "Ident", "LBRACE", "LBRACK", "LPAREN", "NOT", "PRECEDENCE", "String"])));
+    assert_eq!(sets.FirstOf("File"), Some(&set(&["AT", "EOF", "IMPORT", "Ident", "KEYWORD", "START", "TOKEN"])));
+    assert_eq!(sets.IsNullable("File"), Some(true));
+    assert_eq!(sets.IsNullable("Def"), Some(false));
+
+    assert_eq!(sets.FollowOf("File"), Some(&set(&["EOF"])));
+    assert_eq!(sets.FollowOf("Def"), Some(&set(&["EOF", "SEMICOLON"])));
+    assert_eq!(sets.FollowOf("Node"), Some(&set(&["COMMA", "RBRACK", "SEMICOLON"])));
+    assert_eq!(sets.FollowOf("Field"), Some(&set(&["COMMA", "PLUS", "RBRACK", "SEMICOLON", "STAR"])));
+    assert_eq!(sets.FollowOf("Pattern"), Some(&set(&["RBRACE", "SEMICOLON"])));
+}
+
+#[test]
+fn TestGrammarSets_Nullable() {
+    let file = parse(r#"
+File := $a:A, $b:B, EOF
+A := ($items:Ident, COMMA, SEMICOLON)
+B := { DOT => DOT; COLON => ; }
+"#);
+    let symbols = Symbols::Resolve(&file).unwrap();
+
+    let sets = GrammarSets::Compute(&file, &symbols);
+
+    assert_eq!(sets.IsNullable("A"), Some(true));
+    assert_eq!(sets.IsNullable("B"), Some(true));
+    assert_eq!(sets.IsNullable("File"), Some(false));
+    assert_eq!(sets.FirstOf("File"), Some(&set(&["COLON", "DOT", "EOF", "Ident"])));
+    assert_eq!(sets.FollowOf("A"), Some(&set(&["COLON", "DOT", "EOF"])));
+    assert_eq!(sets.SequenceFirst(&file.Definitions.Elements[1].Rule.Elements), (set(&["Ident"]), true));
+}
+
+#[test]
+fn TestCheckConflicts_MetaGrammar() {
+    let file = parse(&fs::read_to_string("proc-parser.ppg").unwrap());
+    let symbols = Symbols::Resolve(&file).unwrap();
+
+    let sets = GrammarSets::Compute(&file, &symbols);
+
+    assert!(sets.CheckConflicts(&file).is_empty());
+    assert!(sets.CheckRecursion(&file).is_empty());
+}
+
+#[test]
+fn TestCheckConflicts() {
+    let file = parse(r#"
+File := LBRACE, $stmt:Stmt, RBRACE, ($args:Arg, COMMA, RPAREN), EOF
+Stmt := {
+    Ident => $call:Call
+    Call => $assign:Ident, COLON
+    DOT => DOT
+}
+Call := Ident, LPAREN
+Arg := { COMMA => COMMA; RPAREN => RPAREN; Ident => Ident; }
+"#);
+    let symbols = Symbols::Resolve(&file).unwrap();
+
+    let sets = GrammarSets::Compute(&file, &symbols);
+    let errors = sets.CheckConflicts(&file);
+    for e in &errors {
+        println!("{:?}", e);
+    }
+
+    assert_eq!(errors.len(), 3);
+    match &errors[0] {
+        ConflictError::List(e) => {
+            assert_eq!(e.Separator.Literal, "RPAREN");
+            assert_eq!(e.Example, vec!["LBRACE", "DOT", "RBRACE", "RPAREN"]);
+        }
+        _ => panic!()
+    }
+    match &errors[1] {
+        ConflictError::List(e) => {
+            assert_eq!(e.Separator.Literal, "COMMA");
+            assert_eq!(e.Example, vec!["LBRACE", "DOT", "RBRACE", "COMMA", "COMMA"]);
+        }
+        _ => panic!()
+    }
+    match &errors[2] {
+        ConflictError::Pattern(e) => {
+            assert_eq!(e.Def, "Stmt");
+            assert_eq!((e.First.Literal.as_str(), e.Second.Literal.as_str()), ("Ident", "Call"));
+            assert_eq!(e.Tokens, set(&["Ident"]));
+            assert_eq!(e.Example, vec!["LBRACE", "Ident"]);
+        }
+        _ => panic!()
+    }
+}
+
+#[test]
+fn TestCheckConflicts_Optional() {
+    let file = parse(r#"
+File := ($stmts:Stmt, SEMICOLON, EOF)
+Stmt := $name:Ident, [COLON, $type:Ident], [DOT, $tag:Ident], [$value:Value]
+Value := { DOT => DOT; Int => Int; }
+"#);
+    let symbols = Symbols::Resolve(&file).unwrap();
+
+    let sets = GrammarSets::Compute(&file, &symbols);
+    assert_eq!(sets.FirstOf("Stmt"), Some(&set(&["Ident"])));
+    assert_eq!(sets.FollowOf("Value"), Some(&set(&["EOF", "SEMICOLON"])));
+
+    let errors = sets.CheckConflicts(&file);
+    for e in &errors {
+        println!("{:?}", e);
+    }
+
+    // DOT can start the tag or, when the tag is left out, the value.
+    match &errors[..] {
+        [ConflictError::Optional(e)] => {
+            assert_eq!(e.Def, "Stmt");
+            assert_eq!(e.Tokens, set(&["DOT"]));
+            assert_eq!(e.Example, vec!["Ident", "DOT"]);
+        }
+        _ => panic!()
+    }
+}
+
+#[test]
+fn TestCheckConflicts_Repeat() {
+    let file = parse(r#"
+File := ($stmts:Stmt+), EOF
+Stmt := $name:Ident, ($args:Int*, COMMA), [COMMA, $tag:Ident], ($words:Ident*), SEMICOLON
+"#);
+    let symbols = Symbols::Resolve(&file).unwrap();
+
+    let sets = GrammarSets::Compute(&file, &symbols);
+    assert_eq!(sets.FirstOf("File"), Some(&set(&["Ident"])));
+    assert_eq!(sets.IsNullable("File"), Some(false));
+    assert_eq!(sets.FollowOf("Stmt"), Some(&set(&["EOF", "Ident"])));
+
+    let errors = sets.CheckConflicts(&file);
+    for e in &errors {
+        println!("{:?}", e);
+    }
+
+    // COMMA after an argument is taken as the delimiter and never starts the tag.
+    match &errors[..] {
+        [ConflictError::Repeat(e)] => {
+            assert_eq!(e.Def, "Stmt");
+            assert_eq!(e.Tokens, set(&["COMMA"]));
+            assert_eq!(e.Example, vec!["Ident", "Int", "COMMA"]);
+        }
+        _ => panic!()
+    }
+}
+
+#[test]
+fn TestCheckConflicts_Lookahead() {
+    let file = parse(r#"
+File := ($stmts:Stmt, SEMICOLON, EOF)
+Stmt := {
+    Ident, COLON => $label:Ident, COLON
+    Ident, LPAREN => $call:Ident, LPAREN, RPAREN
+    Ident => $ref:Ident
+    Int => $int:Int
+    Int, DOT => $float:Int, DOT, Int
+}
+"#);
+    let symbols = Symbols::Resolve(&file).unwrap();
+
+    let sets = GrammarSets::Compute(&file, &symbols);
+    let errors = sets.CheckConflicts(&file);
+    for e in &errors {
+        println!("{:?}", e);
+    }
+
+    // `Ident` falls back after the longer patterns, `Int` shadows `Int, DOT`.
+    assert_eq!(errors.len(), 1);
+    match &errors[0] {
+        ConflictError::Pattern(e) => {
+            assert_eq!((e.First.Pos.Begin.Line, e.Second.Pos.Begin.Line), (6, 7));
+            assert_eq!(e.Example, vec!["Int", "DOT"]);
+        }
+        _ => panic!()
+    }
+}
+
+#[test]
+fn TestCheckConflicts_Predicates() {
+    let file = parse(r#"
+File := ($stmts:Stmt, SEMICOLON, EOF)
+Stmt := {
+    Ident, !LSS => $ref:Ident, [!Ident, $value:Value], ($words:Ident*)
+    Ident, LSS => $generic:Ident, LSS, $arg:Ident, GTR
+    Ident, COMMA => $pair:Ident, COMMA, Ident
+}
+Value := { Ident => $name:Ident; Int => $int:Int; }
+"#);
+    let symbols = Symbols::Resolve(&file).unwrap();
+
+    let sets = GrammarSets::Compute(&file, &symbols);
+    assert_eq!(sets.FirstOf("Stmt"), Some(&set(&["Ident"])));
+
+    let errors = sets.CheckConflicts(&file);
+    for e in &errors {
+        println!("{:?}", e);
+    }
+
+    // `!LSS` tells the first two patterns apart but not the first and the last,
+    // `!Ident` keeps the optional value from taking the words.
+    match &errors[..] {
+        [ConflictError::Pattern(e)] => {
+            assert_eq!((e.First.Pos.Begin.Line, e.Second.Pos.Begin.Line), (3, 5));
+            assert_eq!(e.Example, vec!["Ident", "COMMA"]);
+        }
+        _ => panic!()
+    }
+}
+
+#[test]
+fn TestCheckConflicts_Default() {
+    let file = parse(r#"
+File := ($stmts:Stmt, SEMICOLON, EOF)
+Stmt := {
+    Ident, COLON => $label:Ident, COLON
+    Ident => $ref:Ident
+    _ => $value:Value, $tail:Tail
+}
+Value := { Int => $int:Int; String => $str:String; }
+Tail := { COMMA => COMMA; _ => ; }
+Bad := { _ => $int:Int; Ident => $ref:Ident; }
+"#);
+    let symbols = Symbols::Resolve(&file).unwrap();
+
+    // The default adds the tokens its rule starts with.
+    let sets = GrammarSets::Compute(&file, &symbols);
+    assert_eq!(sets.FirstOf("Stmt"), Some(&set(&["Ident", "Int", "String"])));
+    assert_eq!(sets.FirstOf("Tail"), Some(&set(&["COMMA"])));
+    assert_eq!(sets.IsNullable("Tail"), Some(true));
+
+    let errors = sets.CheckConflicts(&file);
+    for e in &errors {
+        println!("{:?}", e);
+    }
+
+    match &errors[..] {
+        [ConflictError::Shadowed(e)] => {
+            assert_eq!(e.Def, "Bad");
+            assert_eq!(e.Pattern.Literal, "Ident");
+            assert_eq!((e.Default.Pos.Begin.Line, e.Default.Pos.Begin.Column), (9, 9));
+        }
+        _ => panic!()
+    }
+}
+
+#[test]
+fn TestCheckRecursion() {
+    let file = parse(r#"
+File := ($exprs:Expr, SEMICOLON, EOF)
+Expr := $lhs:Expr, DOT, Ident
+Term := ($args:Args, COMMA, RPAREN)
+Args := { LPAREN => $term:Term, Ident; Ident => Ident; }
+Loop := $next:Empty
+Empty := ($loop:Loop, COMMA, SEMICOLON)
+"#);
+    let symbols = Symbols::Resolve(&file).unwrap();
+
+    let sets = GrammarSets::Compute(&file, &symbols);
+    let errors = sets.CheckRecursion(&file);
+    for e in &errors {
+        println!("{:?}", e);
+    }
+
+    let chains: Vec<(bool, Vec<(&str, &str)>)> = errors.iter().map(|e| match e {
+        RecursionError::LeftRecursion(e) => (false, &e.Chain),
+        RecursionError::EmptyCycle(e) => (true, &e.Chain),
+    }).map(|(empty, chain)| {
+        (empty, chain.iter().map(|step| (step.Def.as_str(), step.Call.Literal.as_str())).collect())
+    }).collect();
+
+    assert_eq!(chains, vec![
+        (false, vec![("Expr", "Expr")]),
+        (false, vec![("Term", "Args"), ("Args", "Term")]),
+        (true, vec![("Loop", "Empty"), ("Empty", "Loop")]),
+    ]);
+}
+
+#[test]
+fn TestCheckReachability() {
+    let file = parse(r#"start Stmt
+
+File := ($stmts:Stmt, SEMICOLON, EOF)
+Stmt := $name:Ident, DEFINE, $value:Value
+Value := { Ident => Ident; LPAREN => LPAREN, $inner:Value, RPAREN; }
+Dead := DOT
+"#);
+    let symbols = Symbols::Resolve(&file).unwrap();
+
+    assert_eq!(symbols.Start, Some(1));
+
+    let errors = symbols.CheckReachability(&file);
+    for e in &errors {
+        println!("{:?}", e);
+    }
+
+    let rules: Vec<&str> = errors.iter().filter_map(|e| match e {
+        ReachabilityError::UnreachableRule(e) => Some(e.Name.Literal.as_str()),
+        _ => None
+    }).collect();
+    assert_eq!(rules, vec!["File", "Dead"]);
+
+    let tokens: Vec<&str> = errors.iter().filter_map(|e| match e {
+        ReachabilityError::UnusedToken(e) => Some(e.Kind.Name()),
+        _ => None
+    }).collect();
+    assert!(!tokens.contains(&"DEFINE"));
+    assert!(!tokens.contains(&"DOT"));
+    assert!(tokens.contains(&"ARROW"));
+}
+
+#[test]
+fn TestResolve_Tokens() {
+    let file = parse(r#"start File
+token ASSIGN ":="
+token LPAREN "("
+keyword LET "let"
+
+File := ($stmts:Stmt, SEMICOLON, EOF)
+Stmt := LET, $name:Ident, ASSIGN, $value:Int
+"#);
+
+    let symbols = Symbols::Resolve(&file).unwrap();
+    assert!(matches!(symbols.Lookup("ASSIGN"), Some(Symbol::Token(kind)) if kind.Is(&TokenKind::Declared("ASSIGN"))));
+    assert!(matches!(symbols.Lookup("LPAREN"), Some(Symbol::Token(TokenKind::LPAREN))));
+    assert!(matches!(symbols.Lookup("Ident"), Some(Symbol::Token(TokenKind::Ident))));
+    // Builtin tokens the grammar does not declare are not scanned.
+    assert!(symbols.Lookup("DEFINE").is_none());
+
+    let lookup = symbols.KeywordLookup();
+    assert!(lookup["let"].Is(&TokenKind::Declared("LET")));
+    assert!(lookup[";"].Is(&TokenKind::SEMICOLON));
+    assert!(!lookup.contains_key("=>"));
+
+    let errors = symbols.CheckReachability(&file);
+    let unused: Vec<&str> = errors.iter().filter_map(|e| match e {
+        ReachabilityError::UnusedToken(e) => Some(e.Kind.Name()),
+        _ => None
+    }).collect();
+    assert_eq!(unused, vec!["LPAREN"]);
+}
+
+#[test]
+fn TestResolve_TokenErrors() {
+    let file = parse(r#"token ASSIGN ":="
+token EQUAL ":="
+keyword LET "1et"
+token PAIR "()"
+token Ident "@"
+token File "%"
+
+File := EOF
+"#);
+
+    let errors = Symbols::Resolve(&file).err().unwrap();
+    for e in &errors {
+        println!("{:?}", e);
+    }
+
+    let bad: Vec<&str> = errors.iter().filter_map(|e| match e {
+        ResolveError::BadToken(e) => Some(e.Token.Literal.as_str()),
+        _ => None
+    }).collect();
+    assert_eq!(bad, vec![":=", "1et", "()", "Ident"]);
+
+    assert_eq!(errors.iter().filter(|e| matches!(e, ResolveError::DuplicateDefinition(_))).count(), 1);
+}
+
+#[test]
+fn TestResolve_Precedence() {
+    let file = parse(r#"token PLUS "+"
+token MINUS "-"
+
+File := $a:Expr, $b:Bad, EOF
+Expr := precedence Ident { left PLUS; prefix MINUS; }
+Bad := precedence _ { up PLUS; left File MINUS MINUS; prefix; }
+"#);
+
+    let errors = Symbols::Resolve(&file).err().unwrap();
+    for e in &errors {
+        println!("{:?}", e);
+    }
+
+    let bad: Vec<&str> = errors.iter().filter_map(|e| match e {
+        ResolveError::Precedence(e) if e.Def == "Bad" => Some(e.Token.Literal.as_str()),
+        _ => None
+    }).collect();
+    assert_eq!(bad, vec!["_", "up", "File", "MINUS", "prefix"]);
+    assert_eq!(errors.len(), bad.len());
+
+    let file = parse(r#"token PLUS "+"
+token MINUS "-"
+
+File := $a:Expr, EOF
+Expr := precedence Ident { left PLUS; prefix MINUS; }
+"#);
+    let symbols = Symbols::Resolve(&file).unwrap();
+    let sets = GrammarSets::Compute(&file, &symbols);
+    assert_eq!(sets.FirstOf("Expr").unwrap(), &set(&["Ident", "MINUS"]));
+    assert!(sets.CheckRecursion(&file).is_empty());
+}
+
+#[test]
+fn TestResolve_Literals() {
+    let file = parse(r#"token ASSIGN ":="
+token LPAREN "("
+keyword FN "fn"
+
+File := ($items:Item, SEMICOLON, EOF)
+Item := { "fn" => "fn", $name:Ident, "(", ")"; Ident => $name:Ident, ":=", $value:Int, [")", "->", $type:Ident]; }
+"#);
+
+    let symbols = Symbols::Resolve(&file).unwrap();
+    assert!(matches!(symbols.Lookup("\"fn\""), Some(Symbol::Token(kind)) if kind.Is(&TokenKind::Declared("FN"))));
+    assert!(matches!(symbols.Lookup("\")\""), Some(Symbol::Token(TokenKind::RPAREN))));
+    assert!(matches!(symbols.Lookup("\"->\""), Some(Symbol::Token(kind)) if kind.Is(&TokenKind::Declared("\"->\""))));
+    // Only quoted, `)` is not a declared name.
+    assert!(symbols.Lookup("RPAREN").is_none());
+
+    let lookup = symbols.KeywordLookup();
+    assert!(lookup["->"].Is(&TokenKind::Declared("\"->\"")));
+    assert!(lookup[")"].Is(&TokenKind::RPAREN));
+
+    // Tokens used by their literal are used.
+    assert!(symbols.CheckReachability(&file).is_empty());
+
+    let file = parse("File := \"a b\", \"\", \"1\", EOF\n");
+    let errors = Symbols::Resolve(&file).err().unwrap();
+    let bad: Vec<&str> = errors.iter().filter_map(|e| match e {
+        ResolveError::BadToken(e) => Some(e.Token.Literal.as_str()),
+        _ => None
+    }).collect();
+    assert_eq!(bad, vec!["\"a b\"", "\"\"", "\"1\""]);
+    assert_eq!(errors.len(), 3);
+}
+
+#[test]
+fn TestResolve_Annotations() {
+    let file = parse(r#"@doc("A file.") @name("file") @inline
+@recover(SEMICOLON, ")")
+File := ($items:Item, SEMICOLON, EOF)
+@doc @inline(a) @recover(Item, 1) @name("a") @name("b") @pure
+Item := Ident
+"#);
+
+    let errors = Symbols::Resolve(&file).err().unwrap();
+    let reasons: Vec<String> = errors.iter().map(|e| match e {
+        ResolveError::Annotation(e) => {
+            assert_eq!(e.Def, "Item");
+            e.Reason.clone()
+        }
+        _ => panic!()
+    }).collect();
+    assert_eq!(reasons, vec![
+        "@doc takes a single string",
+        "@inline takes no arguments",
+        "\"Item\" is not a token",
+        "want a token name or a quoted literal",
+        "@name is already given at 4:36 -> 4:40",
+        "unknown annotation @pure, want doc, name, inline or recover",
+    ]);
+
+    let file = parse("@name(\"file\")\nFile := Ident, EOF\n");
+    assert_eq!(file.Definitions.Elements[0].Annotation("name").and_then(Annotation::Text), Some("file"));
+}
+
+#[test]
+fn TestExpandTemplates() {
+    let file = parse(r#"File := ($items:Parens<List<Item, COMMA>>, SEMICOLON, EOF)
+Parens<T> := LPAREN, $inner:T, RPAREN
+List<T, D> := ($items:T*, D)
+Item := { Ident => $name:Ident; LPAREN => $nested:Parens<List<Item, COMMA>>; }
+Unused<T> := $value:Missing
+"#);
+
+    // Templates never instantiated are resolved with their parameters standing for Ident.
+    let messages: Vec<String> = Check(&file).iter().map(|d| d.Message.clone()).collect();
+    assert_eq!(messages, vec!["undefined name \"Missing\""]);
+    let messages: Vec<String> = Check(&parse("File := Ident, EOF\nUnused<T> := $value:T, $next:Parens<T>\nParens<T> := LPAREN, T, RPAREN\n"))
+        .iter().map(|d| format!("{}: {}", d.Severity, d.Message)).collect();
+    assert_eq!(messages, vec![
+        "warning: rule \"Unused\" is unreachable from the start rule",
+        "warning: rule \"Parens\" is unreachable from the start rule",
+    ]);
+
+    let file = ExpandTemplates(&file).unwrap();
+    let names: Vec<&str> = file.Definitions.Elements.iter().map(|def| def.Name.Token.Literal.as_str()).collect();
+    assert_eq!(names, vec!["File", "Item", "ListItemCOMMA", "ParensListItemCOMMA"]);
+    assert!(Check(&file).is_empty());
+
+    let file = parse(r#"File := $a:Pair<A, B>, $b:Pair<AB>, $c:Expr<Ident>, $d:T<Ident>, $e:Pair<"a", B>, $f:Pair, $g:Nest<Ident>, EOF
+Pair<A, B> := $a:A, $b:B
+PairAB := Ident
+Expr := Ident
+Nest<T> := [$next:Nest<Wrap<T>>]
+Wrap<T> := $value:T
+Pair := Ident
+Nest<T, T> := EOF
+"#);
+
+    let errors = ExpandTemplates(&file).err().unwrap();
+    let reasons: Vec<String> = errors.iter().map(|e| match e {
+        ResolveError::Template(e) => e.Reason.clone(),
+        ResolveError::DuplicateDefinition(e) => format!("duplicate {}", e.Name.Literal),
+        _ => panic!()
+    }).collect();
+    assert_eq!(reasons, vec![
+        "duplicate Pair",
+        "duplicate Nest",
+        "Pair<A, B> is named PairAB like PairAB",
+        "Pair is Pair<A, B>, have 1 arguments",
+        "Expr is not a template",
+        "T is not a template",
+        "quoted literal \"a\" cannot be an argument, declare a token for it",
+        "Pair is Pair<A, B>, have 0 arguments",
+        "instances of Wrap nest deeper than 16",
+        "instances of Nest nest deeper than 16",
+    ]);
+}
+
+#[test]
+fn TestResolve_UndefinedStart() {
+    let file = parse("start Missing\nFile := EOF\n");
+
+    let errors = Symbols::Resolve(&file).err().unwrap();
+    assert!(matches!(&errors[..], [ResolveError::UndefinedName(e)] if e.Name.Literal == "Missing"));
+}
+
+#[test]
+fn TestCheck_Messages() {
+    // The conflict message names another position, which must not be taken for its own.
+    let file = parse("File := ($items:Item, SEMICOLON, EOF)\nItem := {\n    Ident => $a:Ident\n    Ident => $b:Ident\n}\n");
+
+    let errors = GrammarSets::Compute(&file, &Symbols::Resolve(&file).unwrap()).CheckConflicts(&file);
+    let diagnostics = Check(&file);
+    match (&errors[..], &diagnostics[..]) {
+        ([ConflictError::Pattern(e)], [diagnostic]) => {
+            assert_eq!(diagnostic.Message, e.to_string());
+            assert_eq!(diagnostic.Message, "conflicting patterns in rule Item: \"Ident\" and \"Ident\" at 3:5 -> 3:10 both start with Ident, e.g. Ident");
+            assert_eq!(format!("{:?}", e), format!("{} {}", e.Second.Pos, diagnostic.Message));
+        }
+        _ => panic!()
+    }
+}
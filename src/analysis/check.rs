@@ -0,0 +1,173 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+
+use crate::analysis::*;
+use crate::ast::*;
+use crate::parser::ParserError;
+use crate::scanner::{BasicScannerError, OverflowError, PosRange};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+// An error or warning about a grammar, located in its source when possible.
+pub struct Diagnostic {
+    pub Severity: Severity,
+    pub Pos: Option<PosRange>,
+    pub Message: String,
+}
+
+impl Diagnostic {
+    // The message of an error is its Display, without the position its Debug starts with.
+    pub fn new(severity: Severity, pos: Option<PosRange>, error: &dyn Display) -> Diagnostic {
+        Diagnostic {
+            Severity: severity,
+            Pos: pos,
+            Message: error.to_string(),
+        }
+    }
+
+    pub fn IsError(&self) -> bool { self.Severity == Severity::Error }
+
+    // `path:line:column: severity: message`, the form compilers and editors understand.
+    pub fn Format(&self, path: &str) -> String {
+        match self.Pos {
+            Some(pos) => format!("{}:{}: {}: {}", path, pos.Begin, self.Severity, self.Message),
+            None => format!("{}: {}: {}", path, self.Severity, self.Message),
+        }
+    }
+
+    // Format with the path of the file it is located in, paths indexed by Position.Source.
+    pub fn FormatIn(&self, paths: &[String]) -> String {
+        let source = self.Pos.map_or(0, |pos| pos.Begin.Source);
+        self.Format(paths.get(source).or(paths.first()).map_or("", String::as_str))
+    }
+}
+
+impl Debug for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.Pos {
+            Some(pos) => write!(f, "{} {}: {}", pos, self.Severity, self.Message),
+            None => write!(f, "{}: {}", self.Severity, self.Message),
+        }
+    }
+}
+
+impl From<&ParserError> for Diagnostic {
+    fn from(e: &ParserError) -> Diagnostic {
+        let pos = Some(e.Pos());
+        match e {
+            ParserError::ScannerError(BasicScannerError::EOF(e)) => Diagnostic::new(Severity::Error, pos, e),
+            ParserError::ScannerError(BasicScannerError::BadFormat(e)) => Diagnostic::new(Severity::Error, pos, e),
+            ParserError::ScannerError(BasicScannerError::BadChar(e)) => Diagnostic::new(Severity::Error, pos, e),
+            ParserError::UnexpectedToken(e) => Diagnostic::new(Severity::Error, pos, e),
+            ParserError::Expected(e) => Diagnostic::new(Severity::Error, pos, e),
+            ParserError::Overflow(e) => Diagnostic::new(Severity::Error, pos, e),
+        }
+    }
+}
+
+impl From<&OverflowError> for Diagnostic {
+    fn from(e: &OverflowError) -> Diagnostic { Diagnostic::new(Severity::Error, Some(e.PosRange), e) }
+}
+
+impl From<&ResolveError> for Diagnostic {
+    fn from(e: &ResolveError) -> Diagnostic {
+        let pos = Some(e.Pos());
+        match e {
+            ResolveError::UndefinedName(e) => Diagnostic::new(Severity::Error, pos, e),
+            ResolveError::DuplicateDefinition(e) => Diagnostic::new(Severity::Error, pos, e),
+            ResolveError::DuplicateField(e) => Diagnostic::new(Severity::Error, pos, e),
+            ResolveError::BadToken(e) => Diagnostic::new(Severity::Error, pos, e),
+            ResolveError::Precedence(e) => Diagnostic::new(Severity::Error, pos, e),
+            ResolveError::Annotation(e) => Diagnostic::new(Severity::Error, pos, e),
+            ResolveError::Template(e) => Diagnostic::new(Severity::Error, pos, e),
+        }
+    }
+}
+
+impl From<&RecursionError> for Diagnostic {
+    fn from(e: &RecursionError) -> Diagnostic {
+        let pos = Some(e.Pos());
+        match e {
+            RecursionError::LeftRecursion(e) => Diagnostic::new(Severity::Error, pos, e),
+            RecursionError::EmptyCycle(e) => Diagnostic::new(Severity::Error, pos, e),
+        }
+    }
+}
+
+impl From<&ConflictError> for Diagnostic {
+    fn from(e: &ConflictError) -> Diagnostic {
+        let pos = Some(e.Pos());
+        match e {
+            ConflictError::Pattern(e) => Diagnostic::new(Severity::Error, pos, e),
+            ConflictError::Shadowed(e) => Diagnostic::new(Severity::Error, pos, e),
+            ConflictError::List(e) => Diagnostic::new(Severity::Error, pos, e),
+            ConflictError::Repeat(e) => Diagnostic::new(Severity::Error, pos, e),
+            ConflictError::Optional(e) => Diagnostic::new(Severity::Error, pos, e),
+        }
+    }
+}
+
+impl From<&ReachabilityError> for Diagnostic {
+    fn from(e: &ReachabilityError) -> Diagnostic {
+        let pos = e.Pos();
+        match e {
+            ReachabilityError::UnreachableRule(e) => Diagnostic::new(Severity::Warning, pos, e),
+            ReachabilityError::UnusedToken(e) => Diagnostic::new(Severity::Warning, pos, e),
+        }
+    }
+}
+
+// Runs every analysis over a parsed grammar, with its templates expanded.
+//
+// Resolution errors stop the check since the other passes need names resolved.
+// Templates never instantiated are resolved as well, and reported as unreachable rules.
+// Unused tokens are only reported for grammars declaring their own.
+pub fn Check(file: &File) -> Vec<Diagnostic> {
+    let templates: HashSet<&str> = file.Definitions.Elements.iter()
+        .filter(|def| !def.Params.is_empty())
+        .map(|def| def.Name.Token.Literal.as_str())
+        .collect();
+
+    // Templates never instantiated are in all only, their errors among those of the file.
+    let (expanded, all) = match (ExpandTemplates(file), ExpandUnusedTemplates(file)) {
+        (Ok(expanded), Ok(all)) => (expanded, all),
+        (Err(errors), _) | (_, Err(errors)) => return errors.iter().map(Diagnostic::from).collect(),
+    };
+    let file = &expanded;
+    let (reachability, symbols) = match (Symbols::Resolve(&all), Symbols::Resolve(file)) {
+        (Ok(reachability), Ok(symbols)) => (reachability, symbols),
+        (Err(errors), _) | (_, Err(errors)) => return errors.iter().map(Diagnostic::from).collect(),
+    };
+
+    let sets = GrammarSets::Compute(file, &symbols);
+
+    let mut diagnostics: Vec<Diagnostic> = vec![];
+    diagnostics.extend(sets.CheckRecursion(file).iter().map(Diagnostic::from));
+    diagnostics.extend(sets.CheckConflicts(file).iter().map(Diagnostic::from));
+    // Instances in templates never instantiated are left to the templates.
+    diagnostics.extend(reachability.CheckReachability(&all).iter()
+        .filter(|e| match e {
+            ReachabilityError::UnreachableRule(e) => symbols.Rules.contains_key(&e.Name.Literal) || templates.contains(e.Name.Literal.as_str()),
+            ReachabilityError::UnusedToken(_) => !file.Tokens.is_empty(),
+        })
+        .map(Diagnostic::from));
+    diagnostics
+}
@@ -0,0 +1,297 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use crate::analysis::*;
+use crate::ast::*;
+
+// Tokens present in both sets, the wildcard overlaps with every token.
+pub fn Overlap(a: &TokenSet, b: &TokenSet) -> TokenSet {
+    if a.contains(WILDCARD) {
+        return b.clone();
+    }
+    if b.contains(WILDCARD) {
+        return a.clone();
+    }
+    a.intersection(b).cloned().collect()
+}
+
+// Shortest token sequences, used to build conflict examples.
+struct Examples {
+    // Shortest input matched by each definition.
+    Yield: Vec<Option<Vec<String>>>,
+    // Shortest input from the start rule up to each definition.
+    Prefix: Vec<Option<Vec<String>>>,
+}
+
+impl Examples {
+    fn Compute(file: &File, symbols: &Symbols) -> Examples {
+        let defs = &file.Definitions.Elements;
+
+        let mut examples = Examples {
+            Yield: vec![None; defs.len()],
+            Prefix: vec![None; defs.len()],
+        };
+
+        loop {
+            let mut changed = false;
+            for (index, def) in defs.iter().enumerate() {
+                if let Some(tokens) = examples.SequenceYield(symbols, &def.Rule.Elements) {
+                    if examples.Yield[index].as_ref().map_or(true, |old| tokens.len() < old.len()) {
+                        examples.Yield[index] = Some(tokens);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        if let Some(start) = symbols.Start {
+            examples.Prefix[start] = Some(vec![]);
+        }
+
+        loop {
+            let mut found: Vec<(usize, Vec<String>)> = vec![];
+            for (index, def) in defs.iter().enumerate() {
+                if let Some(prefix) = &examples.Prefix[index] {
+                    examples.Reach(symbols, &def.Rule.Elements, prefix.clone(), &mut found);
+                }
+            }
+
+            let mut changed = false;
+            for (index, tokens) in found {
+                if examples.Prefix[index].as_ref().map_or(true, |old| tokens.len() < old.len()) {
+                    examples.Prefix[index] = Some(tokens);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        examples
+    }
+
+    fn SymbolYield(&self, symbols: &Symbols, name: &str) -> Option<Vec<String>> {
+        match symbols.Lookup(name)? {
+            Symbol::Rule(index) => self.Yield[index].clone(),
+            Symbol::Token(kind) => Some(vec![kind.Name().to_string()]),
+            Symbol::Any => Some(vec![WILDCARD.to_string()]),
+        }
+    }
+
+    fn NodeYield(&self, symbols: &Symbols, node: &Node) -> Option<Vec<String>> {
+        match node {
+            Node::None => Some(vec![]),
+            Node::Ident(ident) => self.SymbolYield(symbols, &ident.Token.Literal),
+            Node::Field(field) => self.SymbolYield(symbols, &field.Rule.Token.Literal),
+            Node::Match(branch) => branch.Patterns.Elements.iter()
+                .filter_map(|pattern| self.SequenceYield(symbols, &pattern.Rule.Elements))
+                .min_by_key(|tokens| tokens.len()),
+            Node::ListRule(_) | Node::Optional(_) => Some(vec![]),
+            Node::Repeat(repeat) if repeat.MayBeEmpty() => Some(vec![]),
+            Node::Repeat(repeat) => self.SymbolYield(symbols, &repeat.Field.Rule.Token.Literal),
+            Node::Precedence(precedence) => self.SymbolYield(symbols, &precedence.Operand.Token.Literal),
+            Node::Predicate(_) => Some(vec![]),
+        }
+    }
+
+    fn SequenceYield(&self, symbols: &Symbols, nodes: &[Node]) -> Option<Vec<String>> {
+        let mut tokens = vec![];
+        for node in nodes {
+            tokens.extend(self.NodeYield(symbols, node)?);
+        }
+        Some(tokens)
+    }
+
+    fn Reach(&self, symbols: &Symbols, nodes: &[Node], mut prefix: Vec<String>, found: &mut Vec<(usize, Vec<String>)>) {
+        for node in nodes {
+            let name = match node {
+                Node::Ident(ident) => Some(&ident.Token.Literal),
+                Node::Field(field) => Some(&field.Rule.Token.Literal),
+                Node::ListRule(list) => Some(&list.Field.Rule.Token.Literal),
+                Node::Repeat(repeat) => Some(&repeat.Field.Rule.Token.Literal),
+                Node::Precedence(precedence) => Some(&precedence.Operand.Token.Literal),
+                Node::Match(branch) => {
+                    for pattern in &branch.Patterns.Elements {
+                        self.Reach(symbols, &pattern.Rule.Elements, prefix.clone(), found);
+                    }
+                    None
+                }
+                Node::Optional(optional) => {
+                    self.Reach(symbols, &optional.Rule.Elements, prefix.clone(), found);
+                    None
+                }
+                Node::None | Node::Predicate(_) => None,
+            };
+            if let Some(Symbol::Rule(index)) = name.and_then(|name| symbols.Lookup(name)) {
+                found.push((index, prefix.clone()));
+            }
+            if let Some(tokens) = self.NodeYield(symbols, node) {
+                prefix.extend(tokens);
+            }
+        }
+    }
+}
+
+impl GrammarSets {
+    // Every place where the generated parser could not decide with its lookahead:
+    // patterns of a branch, separators of a list, repetitions and optional groups against what follows them.
+    // The default pattern of a branch takes what the others leave, and shadows any pattern after it.
+    // Patterns look as many tokens ahead as they name and their predicates need, lists and optional groups one.
+    pub fn CheckConflicts(&self, file: &File) -> Vec<ConflictError> {
+        let examples = Examples::Compute(file, &self.Symbols);
+        let mut errors: Vec<ConflictError> = vec![];
+
+        for (index, def) in file.Definitions.Elements.iter().enumerate() {
+            let prefix = examples.Prefix[index].clone().unwrap_or_default();
+            self.SequenceConflicts(&examples, &def.Name.Token.Literal, &def.Rule.Elements, prefix, &self.Follow[index], &mut errors);
+        }
+
+        errors
+    }
+
+    // Tokens both patterns accept at each position of the first, None if a position tells them apart.
+    //
+    // A first pattern looking further ahead than the second one is tried first and leaves the rest to it.
+    // Predicates are explicit disambiguation, `A, !B` and `A, B` accept no token in common at their second position.
+    fn PatternOverlap(&self, first: &Pattern, second: &Pattern) -> Option<Vec<TokenSet>> {
+        let (first, second) = (first.AheadTokens(), second.AheadTokens());
+        if first.len() > second.len() {
+            return None;
+        }
+        let mut overlap: Vec<TokenSet> = vec![];
+        for (a, b) in first.iter().zip(&second) {
+            let ((a, a_excluded), (b, b_excluded)) = (self.AheadFirst(a), self.AheadFirst(b));
+            let mut tokens = Overlap(&a, &b);
+            if !tokens.contains(WILDCARD) {
+                tokens.retain(|token| !a_excluded.contains(token) && !b_excluded.contains(token));
+            }
+            if tokens.is_empty() {
+                return None;
+            }
+            overlap.push(tokens);
+        }
+        Some(overlap)
+    }
+
+    // The tail is the FOLLOW set of the sequence.
+    fn SequenceConflicts(&self, examples: &Examples, def: &str, nodes: &[Node], mut prefix: Vec<String>, tail: &TokenSet, errors: &mut Vec<ConflictError>) {
+        for (index, node) in nodes.iter().enumerate() {
+            let (mut after, nullable) = self.SequenceFirst(&nodes[index + 1..]);
+            if nullable {
+                after.extend(tail.iter().cloned());
+            }
+
+            match node {
+                Node::Match(branch) => {
+                    let patterns = &branch.Patterns.Elements;
+                    for (index, first) in patterns.iter().enumerate() {
+                        for second in &patterns[index + 1..] {
+                            if first.IsDefault() {
+                                errors.push(ConflictError::Shadowed(ShadowedPatternError {
+                                    Def: def.to_string(),
+                                    Default: first.AheadBegin().clone(),
+                                    Pattern: second.AheadBegin().clone(),
+                                }));
+                                continue;
+                            }
+                            if second.IsDefault() {
+                                continue;
+                            }
+                            if let Some(mut overlap) = self.PatternOverlap(first, second) {
+                                let mut example = prefix.clone();
+                                example.extend(overlap.iter().map(|tokens| tokens.iter().next().unwrap().clone()));
+                                // Rest of the input the first pattern takes from the second.
+                                for token in &second.AheadTokens()[overlap.len()..] {
+                                    example.extend(self.AheadFirst(token).0.into_iter().next());
+                                }
+                                errors.push(ConflictError::Pattern(PatternConflictError {
+                                    Def: def.to_string(),
+                                    First: first.AheadBegin().clone(),
+                                    Second: second.AheadBegin().clone(),
+                                    Tokens: overlap.swap_remove(0),
+                                    Example: example,
+                                }));
+                            }
+                        }
+                        self.SequenceConflicts(examples, def, &first.Rule.Elements, prefix.clone(), &after, errors);
+                    }
+                }
+                Node::ListRule(list) => {
+                    let element = &list.Field.Rule;
+                    let first = self.SymbolFirst(&element.Token.Literal).0;
+
+                    // The terminator is checked before each element,
+                    // the delimiter is taken after each element.
+                    for (separator, after_element) in [(&list.Term, false), (&list.Delimiter, true)] {
+                        let tokens = Overlap(&first, &self.SymbolFirst(&separator.Token.Literal).0);
+                        if let Some(token) = tokens.iter().next() {
+                            let mut example = prefix.clone();
+                            if after_element {
+                                example.extend(examples.SymbolYield(&self.Symbols, &element.Token.Literal).unwrap_or_default());
+                            }
+                            example.push(token.clone());
+                            errors.push(ConflictError::List(ListConflictError {
+                                Def: def.to_string(),
+                                Separator: separator.Token.clone(),
+                                Element: element.Token.clone(),
+                                Tokens: tokens,
+                                Example: example,
+                            }));
+                        }
+                    }
+                }
+                Node::Repeat(repeat) => {
+                    // After an element the delimiter is taken whenever it is the lookahead,
+                    // then another element is parsed whenever the lookahead can start one.
+                    let element = &repeat.Field.Rule.Token.Literal;
+                    let mut example = prefix.clone();
+                    example.extend(examples.SymbolYield(&self.Symbols, element).unwrap_or_default());
+
+                    let mut continues = vec![];
+                    if let Optional::Some(delimiter) = &repeat.Delimiter {
+                        continues.push((self.SymbolFirst(&delimiter.Token.Literal).0, example.clone()));
+                        example.extend(examples.SymbolYield(&self.Symbols, &delimiter.Token.Literal).unwrap_or_default());
+                    }
+                    continues.push((self.SymbolFirst(element).0, example));
+
+                    for (first, mut example) in continues {
+                        let tokens = Overlap(&first, &after);
+                        if let Some(token) = tokens.iter().next() {
+                            example.push(token.clone());
+                            errors.push(ConflictError::Repeat(RepeatConflictError {
+                                Def: def.to_string(),
+                                Pos: repeat.Pos,
+                                Tokens: tokens,
+                                Example: example,
+                            }));
+                        }
+                    }
+                }
+                Node::Optional(optional) => {
+                    // The group is entered whenever the lookahead can start it.
+                    let tokens = Overlap(&self.SequenceFirst(&optional.Rule.Elements).0, &after);
+                    if let Some(token) = tokens.iter().next() {
+                        let mut example = prefix.clone();
+                        example.push(token.clone());
+                        errors.push(ConflictError::Optional(OptionalConflictError {
+                            Def: def.to_string(),
+                            Pos: optional.Pos,
+                            Tokens: tokens,
+                            Example: example,
+                        }));
+                    }
+                    self.SequenceConflicts(examples, def, &optional.Rule.Elements, prefix.clone(), &after, errors);
+                }
+                _ => {}
+            }
+            if let Some(tokens) = examples.NodeYield(&self.Symbols, node) {
+                prefix.extend(tokens);
+            }
+        }
+    }
+}
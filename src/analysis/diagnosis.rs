@@ -0,0 +1,351 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::analysis::TokenSet;
+use crate::ast::{Token, TokenKind};
+use crate::scanner::PosRange;
+
+pub struct UndefinedNameError {
+    pub Name: Token,
+}
+
+impl fmt::Display for UndefinedNameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "undefined name \"{}\"", self.Name.Literal) }
+}
+
+impl fmt::Debug for UndefinedNameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Name.Pos, self) }
+}
+
+pub struct DuplicateDefinitionError {
+    pub Name: Token,
+    pub Previous: PosRange,
+}
+
+impl fmt::Display for DuplicateDefinitionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "duplicate definition \"{}\", previously defined at {}", self.Name.Literal, self.Previous) }
+}
+
+impl fmt::Debug for DuplicateDefinitionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Name.Pos, self) }
+}
+
+pub struct DuplicateFieldError {
+    pub Def: String,
+    pub Name: Token,
+    pub Previous: PosRange,
+}
+
+impl fmt::Display for DuplicateFieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "duplicate field \"{}\" in rule {}, previously declared at {}", self.Name.Literal, self.Def, self.Previous) }
+}
+
+impl fmt::Debug for DuplicateFieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Name.Pos, self) }
+}
+
+// A `token` or `keyword` declaration the scanner could not honor.
+pub struct BadTokenError {
+    pub Token: Token,
+    pub Reason: String,
+}
+
+impl fmt::Display for BadTokenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "bad token declaration \"{}\": {}", self.Token.Literal, self.Reason) }
+}
+
+impl fmt::Debug for BadTokenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Token.Pos, self) }
+}
+
+pub struct PrecedenceError {
+    pub Def: String,
+    pub Token: Token,
+    pub Reason: String,
+}
+
+impl fmt::Display for PrecedenceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "bad precedence rule {}: {}", self.Def, self.Reason) }
+}
+
+impl fmt::Debug for PrecedenceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Token.Pos, self) }
+}
+
+pub struct AnnotationError {
+    pub Def: String,
+    pub Token: Token,
+    pub Reason: String,
+}
+
+impl fmt::Display for AnnotationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "bad annotation on rule {}: {}", self.Def, self.Reason) }
+}
+
+impl fmt::Debug for AnnotationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Token.Pos, self) }
+}
+
+pub struct TemplateError {
+    pub Def: String,
+    pub Token: Token,
+    pub Reason: String,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "bad template in rule {}: {}", self.Def, self.Reason) }
+}
+
+impl fmt::Debug for TemplateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Token.Pos, self) }
+}
+
+#[derive(Debug)]
+pub enum ResolveError {
+    UndefinedName(UndefinedNameError),
+    DuplicateDefinition(DuplicateDefinitionError),
+    DuplicateField(DuplicateFieldError),
+    BadToken(BadTokenError),
+    Precedence(PrecedenceError),
+    Annotation(AnnotationError),
+    Template(TemplateError),
+}
+
+impl ResolveError {
+    pub fn Pos(&self) -> PosRange {
+        match self {
+            ResolveError::UndefinedName(e) => e.Name.Pos,
+            ResolveError::DuplicateDefinition(e) => e.Name.Pos,
+            ResolveError::DuplicateField(e) => e.Name.Pos,
+            ResolveError::BadToken(e) => e.Token.Pos,
+            ResolveError::Precedence(e) => e.Token.Pos,
+            ResolveError::Annotation(e) => e.Token.Pos,
+            ResolveError::Template(e) => e.Token.Pos,
+        }
+    }
+}
+
+// Two patterns of one branch can be selected by the same lookahead.
+pub struct PatternConflictError {
+    pub Def: String,
+    pub First: Token,
+    pub Second: Token,
+    pub Tokens: TokenSet,
+    pub Example: Vec<String>,
+}
+
+impl fmt::Display for PatternConflictError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "conflicting patterns in rule {}: \"{}\" and \"{}\" at {} both start with {}, e.g. {}",
+               self.Def, self.First.Literal, self.Second.Literal, self.First.Pos,
+               self.Tokens.iter().cloned().collect::<Vec<String>>().join(" "), self.Example.join(" "))
+    }
+}
+
+impl fmt::Debug for PatternConflictError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Second.Pos, self) }
+}
+
+// A pattern after the default pattern of its branch, which takes every token before it.
+pub struct ShadowedPatternError {
+    pub Def: String,
+    pub Default: Token,
+    pub Pattern: Token,
+}
+
+impl fmt::Display for ShadowedPatternError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "pattern \"{}\" in rule {} is shadowed by the default pattern at {}",
+               self.Pattern.Literal, self.Def, self.Default.Pos)
+    }
+}
+
+impl fmt::Debug for ShadowedPatternError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Pattern.Pos, self) }
+}
+
+// The delimiter or terminator of a list can also start its element.
+pub struct ListConflictError {
+    pub Def: String,
+    pub Separator: Token,
+    pub Element: Token,
+    pub Tokens: TokenSet,
+    pub Example: Vec<String>,
+}
+
+impl fmt::Display for ListConflictError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "conflicting list in rule {}: \"{}\" can also start element \"{}\" at {} with {}, e.g. {}",
+               self.Def, self.Separator.Literal, self.Element.Literal, self.Element.Pos,
+               self.Tokens.iter().cloned().collect::<Vec<String>>().join(" "), self.Example.join(" "))
+    }
+}
+
+impl fmt::Debug for ListConflictError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Separator.Pos, self) }
+}
+
+// A token can both continue a repetition, starting an element or being the delimiter, and follow it.
+pub struct RepeatConflictError {
+    pub Def: String,
+    pub Pos: PosRange,
+    pub Tokens: TokenSet,
+    pub Example: Vec<String>,
+}
+
+impl fmt::Display for RepeatConflictError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "conflicting repetition in rule {}: {} can continue it or follow it, e.g. {}",
+               self.Def,
+               self.Tokens.iter().cloned().collect::<Vec<String>>().join(" "), self.Example.join(" "))
+    }
+}
+
+impl fmt::Debug for RepeatConflictError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Pos, self) }
+}
+
+// A token can both start an optional group and follow it.
+pub struct OptionalConflictError {
+    pub Def: String,
+    pub Pos: PosRange,
+    pub Tokens: TokenSet,
+    pub Example: Vec<String>,
+}
+
+impl fmt::Display for OptionalConflictError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "conflicting optional group in rule {}: {} can start it or follow it, e.g. {}",
+               self.Def,
+               self.Tokens.iter().cloned().collect::<Vec<String>>().join(" "), self.Example.join(" "))
+    }
+}
+
+impl fmt::Debug for OptionalConflictError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Pos, self) }
+}
+
+#[derive(Debug)]
+pub enum ConflictError {
+    Pattern(PatternConflictError),
+    Shadowed(ShadowedPatternError),
+    List(ListConflictError),
+    Repeat(RepeatConflictError),
+    Optional(OptionalConflictError),
+}
+
+impl ConflictError {
+    pub fn Pos(&self) -> PosRange {
+        match self {
+            ConflictError::Pattern(e) => e.Second.Pos,
+            ConflictError::Shadowed(e) => e.Pattern.Pos,
+            ConflictError::List(e) => e.Separator.Pos,
+            ConflictError::Repeat(e) => e.Pos,
+            ConflictError::Optional(e) => e.Pos,
+        }
+    }
+}
+
+// A definition referring to the next one of a recursion chain before consuming any token.
+pub struct RecursionStep {
+    pub Def: String,
+    pub Call: Token,
+}
+
+fn WriteChain(f: &mut Formatter<'_>, chain: &[RecursionStep]) -> fmt::Result {
+    write!(f, "{}", chain[0].Def)?;
+    for step in chain {
+        write!(f, " -> {} ({})", step.Call.Literal, step.Call.Pos)?;
+    }
+    Ok(())
+}
+
+// The chain starts and ends at the same definition.
+pub struct LeftRecursionError {
+    pub Chain: Vec<RecursionStep>,
+}
+
+impl fmt::Display for LeftRecursionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "left recursion: ")?;
+        WriteChain(f, &self.Chain)
+    }
+}
+
+impl fmt::Debug for LeftRecursionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Chain[0].Call.Pos, self) }
+}
+
+// Left recursion in which no rule of the chain consumes a token after the call either.
+pub struct EmptyCycleError {
+    pub Chain: Vec<RecursionStep>,
+}
+
+impl fmt::Display for EmptyCycleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "cycle consuming no tokens: ")?;
+        WriteChain(f, &self.Chain)
+    }
+}
+
+impl fmt::Debug for EmptyCycleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Chain[0].Call.Pos, self) }
+}
+
+#[derive(Debug)]
+pub enum RecursionError {
+    LeftRecursion(LeftRecursionError),
+    EmptyCycle(EmptyCycleError),
+}
+
+impl RecursionError {
+    pub fn Pos(&self) -> PosRange {
+        match self {
+            RecursionError::LeftRecursion(e) => e.Chain[0].Call.Pos,
+            RecursionError::EmptyCycle(e) => e.Chain[0].Call.Pos,
+        }
+    }
+}
+
+pub struct UnreachableRuleError {
+    pub Name: Token,
+}
+
+impl fmt::Display for UnreachableRuleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "rule \"{}\" is unreachable from the start rule", self.Name.Literal) }
+}
+
+impl fmt::Debug for UnreachableRuleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Name.Pos, self) }
+}
+
+pub struct UnusedTokenError {
+    pub Kind: TokenKind,
+}
+
+impl fmt::Display for UnusedTokenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "token {} {} is never used", self.Kind.Name(), self.Kind) }
+}
+
+impl fmt::Debug for UnusedTokenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{}", self) }
+}
+
+#[derive(Debug)]
+pub enum ReachabilityError {
+    UnreachableRule(UnreachableRuleError),
+    UnusedToken(UnusedTokenError),
+}
+
+impl ReachabilityError {
+    pub fn Pos(&self) -> Option<PosRange> {
+        match self {
+            ReachabilityError::UnreachableRule(e) => Some(e.Name.Pos),
+            ReachabilityError::UnusedToken(_) => None,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+pub use check::*;
+pub use conflict::*;
+pub use diagnosis::*;
+pub use reachability::*;
+pub use recursion::*;
+pub use resolve::*;
+pub use sets::*;
+pub use template::*;
+
+pub mod check;
+pub mod conflict;
+pub mod diagnosis;
+pub mod reachability;
+pub mod recursion;
+pub mod resolve;
+pub mod sets;
+pub mod template;
+mod analysis_test;
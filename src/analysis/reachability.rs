@@ -0,0 +1,103 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::collections::HashSet;
+
+use crate::analysis::*;
+use crate::ast::*;
+
+impl Symbols {
+    // Definitions never reached from the start rule and
+    // tokens of the grammar, or of TokenKind::KeywordLookup without declarations, that no rule refers to.
+    pub fn CheckReachability(&self, file: &File) -> Vec<ReachabilityError> {
+        let defs = &file.Definitions.Elements;
+
+        let mut names: Vec<Vec<&str>> = vec![];
+        for def in defs {
+            let mut refs = vec![];
+            References(&def.Rule.Elements, &mut refs);
+            names.push(refs);
+        }
+
+        let mut reachable = vec![false; defs.len()];
+        let mut stack: Vec<usize> = self.Start.into_iter().collect();
+        while let Some(index) = stack.pop() {
+            if reachable[index] {
+                continue;
+            }
+            reachable[index] = true;
+            for name in &names[index] {
+                if let Some(Symbol::Rule(callee)) = self.Lookup(name) {
+                    stack.push(callee);
+                }
+            }
+        }
+
+        let mut errors: Vec<ReachabilityError> = vec![];
+
+        for (index, def) in defs.iter().enumerate() {
+            if !reachable[index] {
+                errors.push(ReachabilityError::UnreachableRule(UnreachableRuleError {
+                    Name: def.Name.Token.clone(),
+                }));
+            }
+        }
+
+        // Quoted literals use the token of their literal.
+        let used: HashSet<String> = names.iter().flatten().map(|name| match Unquote(name).and(self.Lookup(name)) {
+            Some(Symbol::Token(kind)) => kind.Name().to_string(),
+            _ => name.to_string()
+        }).collect();
+        let tokens: Vec<TokenKind> = if self.Tokens.is_empty() {
+            TokenKind::KeywordLookup().into_values().collect()
+        } else {
+            self.Tokens.values().cloned().collect()
+        };
+        let mut unused: Vec<TokenKind> = tokens.into_iter()
+            .filter(|kind| !used.contains(kind.Name()))
+            .collect();
+        unused.sort_by(|a, b| a.Name().cmp(b.Name()));
+        for kind in unused {
+            errors.push(ReachabilityError::UnusedToken(UnusedTokenError { Kind: kind }));
+        }
+
+        errors
+    }
+}
+
+// Every rule or token name a sequence refers to, including lookahead of patterns.
+fn References<'a>(nodes: &'a [Node], refs: &mut Vec<&'a str>) {
+    for node in nodes {
+        match node {
+            Node::None => {}
+            Node::Ident(ident) => refs.push(&ident.Token.Literal),
+            Node::Field(field) => refs.push(&field.Rule.Token.Literal),
+            Node::Match(branch) => {
+                for pattern in &branch.Patterns.Elements {
+                    References(&pattern.Ahead.Elements, refs);
+                    References(&pattern.Rule.Elements, refs);
+                }
+            }
+            Node::ListRule(list) => {
+                refs.push(&list.Field.Rule.Token.Literal);
+                refs.push(&list.Delimiter.Token.Literal);
+                refs.push(&list.Term.Token.Literal);
+            }
+            Node::Repeat(repeat) => {
+                refs.push(&repeat.Field.Rule.Token.Literal);
+                if let Optional::Some(delimiter) = &repeat.Delimiter {
+                    refs.push(&delimiter.Token.Literal);
+                }
+            }
+            Node::Optional(optional) => References(&optional.Rule.Elements, refs),
+            Node::Precedence(precedence) => {
+                refs.push(&precedence.Operand.Token.Literal);
+                for level in &precedence.Levels.Elements {
+                    refs.extend(level.Operators.iter().map(|operator| operator.Token.Literal.as_str()));
+                }
+            }
+            Node::Predicate(predicate) => refs.push(&predicate.Rule.Token.Literal),
+        }
+    }
+}
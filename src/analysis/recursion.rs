@@ -0,0 +1,118 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::collections::VecDeque;
+
+use crate::analysis::*;
+use crate::ast::*;
+
+// A reference made before the calling rule consumed any token.
+struct LeftCall {
+    Callee: usize,
+    Call: Token,
+    // Whether the caller may also consume nothing after the call returns.
+    TailNullable: bool,
+}
+
+impl GrammarSets {
+    // Every recursion reachable without consuming a token,
+    // which would overflow the stack of a recursive descent parser.
+    pub fn CheckRecursion(&self, file: &File) -> Vec<RecursionError> {
+        let defs = &file.Definitions.Elements;
+
+        let calls: Vec<Vec<LeftCall>> = defs.iter().map(|def| {
+            let mut calls = vec![];
+            self.LeftCalls(&def.Rule.Elements, true, &mut calls);
+            calls
+        }).collect();
+
+        let mut errors: Vec<RecursionError> = vec![];
+
+        // Shortest cycle through each definition, reported once by the lowest definition on it.
+        for start in 0..defs.len() {
+            let mut parent: Vec<Option<(usize, usize)>> = vec![None; defs.len()];
+            let mut queue = VecDeque::from([start]);
+            let mut end: Option<(usize, usize)> = None;
+
+            'search: while let Some(caller) = queue.pop_front() {
+                for (index, call) in calls[caller].iter().enumerate() {
+                    if call.Callee == start {
+                        end = Some((caller, index));
+                        break 'search;
+                    }
+                    if call.Callee > start && parent[call.Callee].is_none() {
+                        parent[call.Callee] = Some((caller, index));
+                        queue.push_back(call.Callee);
+                    }
+                }
+            }
+
+            let Some(mut edge) = end else { continue; };
+
+            let mut chain: Vec<RecursionStep> = vec![];
+            let mut empty = true;
+            loop {
+                let (caller, index) = edge;
+                let call = &calls[caller][index];
+                empty &= call.TailNullable;
+                chain.push(RecursionStep {
+                    Def: defs[caller].Name.Token.Literal.clone(),
+                    Call: call.Call.clone(),
+                });
+                match parent[caller] {
+                    Some(previous) if caller != start => edge = previous,
+                    _ => break
+                }
+            }
+            chain.reverse();
+
+            errors.push(if empty {
+                RecursionError::EmptyCycle(EmptyCycleError { Chain: chain })
+            } else {
+                RecursionError::LeftRecursion(LeftRecursionError { Chain: chain })
+            });
+        }
+
+        errors
+    }
+
+    fn LeftCalls(&self, nodes: &[Node], tail_nullable: bool, calls: &mut Vec<LeftCall>) {
+        for (index, node) in nodes.iter().enumerate() {
+            let rest_nullable = tail_nullable && self.SequenceFirst(&nodes[index + 1..]).1;
+
+            let call = match node {
+                Node::Ident(ident) => Some(&ident.Token),
+                Node::Field(field) => Some(&field.Rule.Token),
+                Node::ListRule(list) => Some(&list.Field.Rule.Token),
+                Node::Repeat(repeat) => Some(&repeat.Field.Rule.Token),
+                Node::Match(branch) => {
+                    for pattern in &branch.Patterns.Elements {
+                        self.LeftCalls(&pattern.Rule.Elements, rest_nullable, calls);
+                    }
+                    None
+                }
+                Node::Optional(optional) => {
+                    self.LeftCalls(&optional.Rule.Elements, rest_nullable, calls);
+                    None
+                }
+                // Operands after an operator are not left calls.
+                Node::Precedence(precedence) => Some(&precedence.Operand.Token),
+                Node::None | Node::Predicate(_) => None,
+            };
+            if let Some(call) = call {
+                if let Some(Symbol::Rule(callee)) = self.Symbols.Lookup(&call.Literal) {
+                    calls.push(LeftCall {
+                        Callee: callee,
+                        Call: call.clone(),
+                        TailNullable: rest_nullable,
+                    });
+                }
+            }
+
+            if !self.NodeFirst(node).1 {
+                break;
+            }
+        }
+    }
+}
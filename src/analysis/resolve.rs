@@ -0,0 +1,412 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::collections::HashMap;
+
+use crate::analysis::*;
+use crate::ast::*;
+use crate::parser::Parser;
+use crate::scanner::{BasicTokenKind, PosRange};
+
+// Name matching any single token, e.g. the trailing `_` in `Branch := LBRACE, (...), _`.
+pub const WILDCARD: &str = "_";
+
+#[derive(Clone, Debug)]
+pub enum Symbol {
+    // Index into File.Definitions.Elements.
+    Rule(usize),
+    Token(TokenKind),
+    Any,
+}
+
+// Kinds the scanner produces by itself, available whether or not a grammar declares its tokens.
+fn Lexical(kind: &TokenKind) -> bool {
+    matches!(kind, TokenKind::EOF | TokenKind::Ident | TokenKind::Operator | TokenKind::Int(_) |
+        TokenKind::Float | TokenKind::String | TokenKind::Char | TokenKind::SEMICOLON)
+}
+
+// The basic token kind a literal is scanned as, if it is scanned as exactly one token.
+fn ScanLiteral(literal: &str) -> Option<BasicTokenKind> {
+    let mut p = Parser::new(literal.chars().collect());
+    let token = p.Scanner.Scan().ok()?;
+    if token.Literal.iter().collect::<String>() != literal || p.Scanner.Scan().is_ok() {
+        return None;
+    }
+    Some(token.Kind)
+}
+
+// The literal of a quoted name such as `":="`, see Ident::ExpectSymbol.
+pub fn Unquote(name: &str) -> Option<&str> {
+    name.strip_prefix('"')?.strip_suffix('"')
+}
+
+#[derive(Clone)]
+pub struct Symbols {
+    pub Rules: HashMap<String, usize>,
+    // The rule a whole input is parsed as.
+    pub Start: Option<usize>,
+    // Tokens declared by the grammar and their literals,
+    // the builtin token kinds are used by grammars declaring none.
+    // Literals also holds those quoted in rules, see Resolver::Literal, and the builtin keywords they name.
+    pub Tokens: HashMap<String, TokenKind>,
+    pub Literals: HashMap<String, TokenKind>,
+    // Whether rules name Char, scanning single quotes as char literals rather than strings.
+    pub CharLiterals: bool,
+}
+
+impl Symbols {
+    // Definitions shadow token kinds of the same name.
+    pub fn Lookup(&self, name: &str) -> Option<Symbol> {
+        if name == WILDCARD {
+            return Some(Symbol::Any);
+        }
+        if let Some(literal) = Unquote(name) {
+            return self.Literals.get(literal).cloned().map(Symbol::Token);
+        }
+        if let Some(index) = self.Rules.get(name) {
+            return Some(Symbol::Rule(*index));
+        }
+        if self.Tokens.is_empty() {
+            return TokenKind::FromName(name).map(Symbol::Token);
+        }
+        if let Some(kind) = self.Tokens.get(name) {
+            return Some(Symbol::Token(kind.clone()));
+        }
+        TokenKind::FromName(name).filter(Lexical).map(Symbol::Token)
+    }
+
+    // Table for Parser.KeywordLookup.
+    //
+    // `;` keeps its meaning in grammars declaring their own tokens,
+    // since the parser completes semicolons at line ends.
+    pub fn KeywordLookup(&self) -> HashMap<String, TokenKind> {
+        let mut lookup = match self.Tokens.is_empty() {
+            true => TokenKind::KeywordLookup(),
+            false => HashMap::from([(";".to_string(), TokenKind::SEMICOLON)]),
+        };
+        lookup.extend(self.Literals.iter().map(|(literal, kind)| (literal.clone(), kind.clone())));
+        lookup
+    }
+
+    fn Declare(&mut self, decl: &TokenDef, positions: &mut HashMap<String, PosRange>, literals: &mut HashMap<String, PosRange>, errors: &mut Vec<ResolveError>) {
+        let name = &decl.Name.Token.Literal;
+        let literal = &decl.Literal.Literal;
+
+        let mut bad = |token: &Token, reason: &str| errors.push(ResolveError::BadToken(BadTokenError {
+            Token: token.clone(),
+            Reason: reason.to_string(),
+        }));
+
+        let kind = TokenKind::Declare(name);
+        if Lexical(&kind) || name == WILDCARD {
+            bad(&decl.Name.Token, "name is reserved");
+            return;
+        }
+        match (&decl.Kind.Kind, ScanLiteral(literal)) {
+            (TokenKind::KEYWORD, Some(BasicTokenKind::Ident)) => {}
+            (TokenKind::KEYWORD, _) => return bad(&decl.Literal, "keyword must be an identifier"),
+            (_, Some(BasicTokenKind::Operator | BasicTokenKind::Delimiter)) if literal != "\n" => {}
+            (_, _) => return bad(&decl.Literal, "token must be punctuation scanned as a single token"),
+        }
+        if let Some(previous) = literals.get(literal) {
+            return bad(&decl.Literal, &format!("literal is already declared at {}", previous));
+        }
+
+        match positions.get(name) {
+            Some(previous) => errors.push(ResolveError::DuplicateDefinition(DuplicateDefinitionError {
+                Name: decl.Name.Token.clone(),
+                Previous: *previous,
+            })),
+            None => {
+                positions.insert(name.clone(), decl.Name.Pos);
+                literals.insert(literal.clone(), decl.Literal.Pos);
+                self.Tokens.insert(name.clone(), kind.clone());
+                self.Literals.insert(literal.clone(), kind);
+            }
+        }
+    }
+
+    pub fn Resolve(file: &File) -> Result<Symbols, Vec<ResolveError>> {
+        let mut errors: Vec<ResolveError> = vec![];
+        let mut rules: HashMap<String, usize> = HashMap::new();
+        let mut positions: HashMap<String, PosRange> = HashMap::new();
+
+        let mut symbols = Symbols {
+            Rules: HashMap::new(),
+            Start: None,
+            Tokens: HashMap::new(),
+            Literals: HashMap::new(),
+            CharLiterals: false,
+        };
+
+        let mut literals: HashMap<String, PosRange> = HashMap::new();
+        for decl in &file.Tokens {
+            symbols.Declare(decl, &mut positions, &mut literals, &mut errors);
+        }
+
+        for (index, def) in file.Definitions.Elements.iter().enumerate() {
+            let name = &def.Name.Token.Literal;
+            match positions.get(name) {
+                Some(previous) => errors.push(ResolveError::DuplicateDefinition(DuplicateDefinitionError {
+                    Name: def.Name.Token.clone(),
+                    Previous: *previous,
+                })),
+                None => {
+                    rules.insert(name.clone(), index);
+                    positions.insert(name.clone(), def.Name.Pos);
+                }
+            }
+        }
+
+        // Without `start`, `File` if defined, otherwise the first definition.
+        let start = match &file.Start {
+            Optional::Some(name) => {
+                let index = rules.get(&name.Token.Literal).copied();
+                if index.is_none() {
+                    errors.push(ResolveError::UndefinedName(UndefinedNameError {
+                        Name: name.Token.clone(),
+                    }));
+                }
+                index
+            }
+            Optional::None => match rules.get("File") {
+                Some(index) => Some(*index),
+                None => if file.Definitions.Elements.is_empty() { None } else { Some(0) }
+            }
+        };
+
+        symbols.Rules = rules;
+        symbols.Start = start;
+
+        let mut quoted: HashMap<String, TokenKind> = HashMap::new();
+        let mut chars = false;
+        for def in &file.Definitions.Elements {
+            let mut resolver = Resolver {
+                Symbols: &symbols,
+                Def: &def.Name.Token.Literal,
+                Errors: &mut errors,
+                Quoted: &mut quoted,
+                Chars: &mut chars,
+            };
+            resolver.Annotations(&def.Annotations);
+            match &def.Rule.Elements[..] {
+                [Node::Precedence(precedence)] => resolver.Precedence(precedence),
+                nodes => resolver.Sequence(nodes, &mut HashMap::new()),
+            }
+        }
+        symbols.Literals.extend(quoted);
+        symbols.CharLiterals = chars;
+
+        if errors.is_empty() {
+            Ok(symbols)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+struct Resolver<'a> {
+    Symbols: &'a Symbols,
+    Def: &'a str,
+    Errors: &'a mut Vec<ResolveError>,
+    // Kinds of the literals quoted in rules and not declared, and of the builtin keywords named,
+    // added to Symbols.Literals.
+    Quoted: &'a mut HashMap<String, TokenKind>,
+    // Whether a rule names Char, see Symbols.CharLiterals.
+    Chars: &'a mut bool,
+}
+
+impl<'a> Resolver<'a> {
+    fn Name(&mut self, ident: &Ident) {
+        if let Some(literal) = Unquote(&ident.Token.Literal) {
+            return self.Literal(ident, literal);
+        }
+        match self.Symbols.Lookup(&ident.Token.Literal) {
+            Some(Symbol::Token(TokenKind::Char)) => *self.Chars = true,
+            // A grammar naming a builtin keyword scans its literal as one.
+            Some(Symbol::Token(kind)) => if let Some(literal) = kind.Keyword() {
+                self.Quoted.insert(literal.to_string(), kind);
+            }
+            Some(_) => {}
+            None => self.Errors.push(ResolveError::UndefinedName(UndefinedNameError {
+                Name: ident.Token.clone(),
+            }))
+        }
+    }
+
+    // A literal quoted in a rule is the builtin or declared token of that literal if any,
+    // otherwise a token of its own named by the quoted literal, which diagnostics show.
+    fn Literal(&mut self, ident: &Ident, literal: &str) {
+        if self.Symbols.Literals.contains_key(literal) || self.Quoted.contains_key(literal) {
+            return;
+        }
+        let kind = match (TokenKind::MetaKeywordLookup().remove(literal), ScanLiteral(literal)) {
+            (Some(kind), _) if literal != "\n" => kind,
+            (_, Some(BasicTokenKind::Ident | BasicTokenKind::Operator | BasicTokenKind::Delimiter)) if literal != "\n" => {
+                TokenKind::Declare(&ident.Token.Literal)
+            }
+            (_, _) => {
+                self.Errors.push(ResolveError::BadToken(BadTokenError {
+                    Token: ident.Token.clone(),
+                    Reason: "literal must be an identifier or punctuation scanned as a single token".to_string(),
+                }));
+                return;
+            }
+        };
+        self.Quoted.insert(literal.to_string(), kind);
+    }
+
+    fn BadAnnotation(&mut self, token: &Token, reason: &str) {
+        self.Errors.push(ResolveError::Annotation(AnnotationError {
+            Def: self.Def.to_string(),
+            Token: token.clone(),
+            Reason: reason.to_string(),
+        }));
+    }
+
+    // `@doc("...")` and `@name("...")` take a string, `@inline` nothing,
+    // `@recover(...)` the names or quoted literals of tokens.
+    fn Annotations(&mut self, annotations: &[Annotation]) {
+        let mut seen: HashMap<&str, PosRange> = HashMap::new();
+        for annotation in annotations {
+            let name = &annotation.Name.Token;
+            if let Some(previous) = seen.insert(&name.Literal, name.Pos) {
+                self.BadAnnotation(name, &format!("@{} is already given at {}", name.Literal, previous));
+            }
+
+            let args = &annotation.Args.Elements;
+            match name.Literal.as_str() {
+                "doc" | "name" => if annotation.Text().is_none() {
+                    self.BadAnnotation(name, &format!("@{} takes a single string", name.Literal));
+                }
+                "inline" => if !args.is_empty() {
+                    self.BadAnnotation(&args[0], "@inline takes no arguments");
+                }
+                "recover" => {
+                    if args.is_empty() {
+                        self.BadAnnotation(name, "@recover takes at least one token");
+                    }
+                    for arg in args {
+                        let mut token = arg.clone();
+                        match arg.Kind {
+                            TokenKind::Ident => {}
+                            TokenKind::String => token.Literal = format!("\"{}\"", arg.Literal),
+                            _ => {
+                                self.BadAnnotation(arg, "want a token name or a quoted literal");
+                                continue;
+                            }
+                        }
+                        if let Some(Symbol::Rule(_) | Symbol::Any) = self.Symbols.Lookup(&token.Literal) {
+                            self.BadAnnotation(arg, &format!("\"{}\" is not a token", token.Literal));
+                        }
+                        self.Name(&Ident { Pos: token.Pos, Token: token, Args: vec![] });
+                    }
+                }
+                _ => self.BadAnnotation(name, &format!("unknown annotation @{}, want doc, name, inline or recover", name.Literal)),
+            }
+        }
+    }
+
+    fn BadPrecedence(&mut self, token: &Token, reason: String) {
+        self.Errors.push(ResolveError::Precedence(PrecedenceError {
+            Def: self.Def.to_string(),
+            Token: token.clone(),
+            Reason: reason,
+        }));
+    }
+
+    // Operators must be tokens, each used once as a prefix and once as a binary operator at most.
+    fn Precedence(&mut self, precedence: &PrecedenceRule) {
+        self.Name(&precedence.Operand);
+        if let Some(Symbol::Any) = self.Symbols.Lookup(&precedence.Operand.Token.Literal) {
+            self.BadPrecedence(&precedence.Operand.Token, "operand must be a rule or a token".to_string());
+        }
+
+        let mut prefix: HashMap<&str, PosRange> = HashMap::new();
+        let mut binary: HashMap<&str, PosRange> = HashMap::new();
+        for level in &precedence.Levels.Elements {
+            let assoc = &level.Assoc.Token;
+            let seen = match assoc.Literal.as_str() {
+                "left" | "right" => &mut binary,
+                "prefix" => &mut prefix,
+                _ => {
+                    self.BadPrecedence(assoc, format!("unknown associativity \"{}\", want left, right or prefix", assoc.Literal));
+                    continue;
+                }
+            };
+            if level.Operators.is_empty() {
+                self.BadPrecedence(assoc, "level without operators".to_string());
+            }
+
+            for operator in &level.Operators {
+                let name = operator.Token.Literal.as_str();
+                match self.Symbols.Lookup(name) {
+                    Some(Symbol::Token(_)) => {}
+                    None => self.Name(operator),
+                    Some(_) => self.BadPrecedence(&operator.Token, format!("operator \"{}\" is not a token", name)),
+                }
+                match seen.get(name) {
+                    Some(previous) => {
+                        let reason = format!("operator \"{}\" already has a precedence at {}", name, previous);
+                        self.BadPrecedence(&operator.Token, reason);
+                    }
+                    None => { seen.insert(name, operator.Pos); }
+                }
+            }
+        }
+    }
+
+    fn Field(&mut self, field: &Field, fields: &mut HashMap<String, PosRange>) {
+        let name = &field.Name.Token.Literal;
+        match fields.get(name) {
+            Some(previous) => self.Errors.push(ResolveError::DuplicateField(DuplicateFieldError {
+                Def: self.Def.to_string(),
+                Name: field.Name.Token.clone(),
+                Previous: *previous,
+            })),
+            None => { fields.insert(name.clone(), field.Name.Pos); }
+        }
+        self.Name(&field.Rule);
+    }
+
+    // Fields are scoped to the whole definition, including optional groups, except that
+    // alternative patterns of a branch may reuse the same name.
+    fn Sequence(&mut self, nodes: &[Node], fields: &mut HashMap<String, PosRange>) {
+        for node in nodes {
+            match node {
+                Node::None => {}
+                Node::Ident(ident) => self.Name(ident),
+                Node::Field(field) => self.Field(field, fields),
+                Node::Match(branch) => {
+                    let mut merged = fields.clone();
+                    for pattern in &branch.Patterns.Elements {
+                        let mut scope = fields.clone();
+                        self.Sequence(&pattern.Ahead.Elements, &mut scope);
+                        self.Sequence(&pattern.Rule.Elements, &mut scope);
+                        for (name, pos) in scope {
+                            merged.entry(name).or_insert(pos);
+                        }
+                    }
+                    *fields = merged;
+                }
+                Node::ListRule(list) => {
+                    self.Field(&list.Field, fields);
+                    self.Name(&list.Delimiter);
+                    self.Name(&list.Term);
+                }
+                Node::Repeat(repeat) => {
+                    self.Field(&repeat.Field, fields);
+                    if let Optional::Some(delimiter) = &repeat.Delimiter {
+                        self.Name(delimiter);
+                    }
+                }
+                Node::Optional(optional) => self.Sequence(&optional.Rule.Elements, fields),
+                Node::Precedence(precedence) => {
+                    self.BadPrecedence(&precedence.Operand.Token, "precedence must be the whole rule".to_string());
+                }
+                Node::Predicate(predicate) => self.Name(&predicate.Rule),
+            }
+        }
+    }
+}
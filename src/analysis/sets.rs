@@ -0,0 +1,260 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::collections::BTreeSet;
+
+use crate::analysis::*;
+use crate::ast::*;
+
+// Token kinds by name, see TokenKind::Name.
+// The wildcard `_` stands for any token.
+pub type TokenSet = BTreeSet<String>;
+
+// FIRST, FOLLOW and nullable sets of every definition, indexed like File.Definitions.Elements.
+//
+// A branch starts with the lookahead of its patterns, or the FIRST set of the rule of its default pattern,
+// and is nullable when the rule of any pattern is.
+// A list `($f:T, DELIM, TERM)` is nullable and starts with FIRST(T),
+// its terminator is not consumed and must be matched by what follows the list.
+// A repetition `($f:T*, DELIM)` starts with FIRST(T) and is nullable unless it is `+`,
+// it stops where neither the delimiter nor FIRST(T) follows an element.
+// An optional group `[...]` is nullable and starts with the FIRST set of its rule.
+// A predicate `&X` or `!X` is nullable and narrows the FIRST sets of what follows it up to the next token,
+// to FIRST(X) or to the tokens outside FIRST(X), the latter unknown to sets holding the wildcard.
+pub struct GrammarSets {
+    pub Symbols: Symbols,
+    pub Nullable: Vec<bool>,
+    pub First: Vec<TokenSet>,
+    pub Follow: Vec<TokenSet>,
+}
+
+impl GrammarSets {
+    pub fn Compute(file: &File, symbols: &Symbols) -> GrammarSets {
+        let defs = &file.Definitions.Elements;
+
+        let mut sets = GrammarSets {
+            Symbols: symbols.clone(),
+            Nullable: vec![false; defs.len()],
+            First: vec![TokenSet::new(); defs.len()],
+            Follow: vec![TokenSet::new(); defs.len()],
+        };
+
+        loop {
+            let mut changed = false;
+            for (index, def) in defs.iter().enumerate() {
+                let (first, nullable) = sets.SequenceFirst(&def.Rule.Elements);
+                if nullable && !sets.Nullable[index] {
+                    sets.Nullable[index] = true;
+                    changed = true;
+                }
+                if !first.is_subset(&sets.First[index]) {
+                    sets.First[index].extend(first);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        if let Some(start) = symbols.Start {
+            sets.Follow[start].insert(TokenKind::EOF.Name().to_string());
+        }
+
+        loop {
+            let mut changed = false;
+            for (index, def) in defs.iter().enumerate() {
+                let tail = sets.Follow[index].clone();
+                changed |= sets.SequenceFollow(&def.Rule.Elements, &tail);
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        sets
+    }
+
+    pub fn FirstOf(&self, name: &str) -> Option<&TokenSet> {
+        match self.Symbols.Lookup(name) {
+            Some(Symbol::Rule(index)) => Some(&self.First[index]),
+            _ => None
+        }
+    }
+
+    pub fn FollowOf(&self, name: &str) -> Option<&TokenSet> {
+        match self.Symbols.Lookup(name) {
+            Some(Symbol::Rule(index)) => Some(&self.Follow[index]),
+            _ => None
+        }
+    }
+
+    pub fn IsNullable(&self, name: &str) -> Option<bool> {
+        match self.Symbols.Lookup(name) {
+            Some(Symbol::Rule(index)) => Some(self.Nullable[index]),
+            _ => None
+        }
+    }
+
+    // FIRST set of a rule or token name and whether it is nullable.
+    pub fn SymbolFirst(&self, name: &str) -> (TokenSet, bool) {
+        match self.Symbols.Lookup(name) {
+            Some(Symbol::Rule(index)) => (self.First[index].clone(), self.Nullable[index]),
+            Some(Symbol::Token(kind)) => (TokenSet::from([kind.Name().to_string()]), false),
+            Some(Symbol::Any) => (TokenSet::from([WILDCARD.to_string()]), false),
+            None => (TokenSet::new(), false) // reported by Symbols::Resolve
+        }
+    }
+
+    pub fn NodeFirst(&self, node: &Node) -> (TokenSet, bool) {
+        match node {
+            Node::None => (TokenSet::new(), true),
+            Node::Ident(ident) => self.SymbolFirst(&ident.Token.Literal),
+            Node::Field(field) => self.SymbolFirst(&field.Rule.Token.Literal),
+            Node::Match(branch) => {
+                let mut first = TokenSet::new();
+                let mut nullable = false;
+                for pattern in &branch.Patterns.Elements {
+                    let (rule_first, rule_nullable) = self.SequenceFirst(&pattern.Rule.Elements);
+                    match pattern.IsDefault() {
+                        true => first.extend(rule_first),
+                        false => first.extend(self.AheadFirst(&pattern.AheadTokens()[0]).0),
+                    }
+                    nullable |= rule_nullable;
+                }
+                (first, nullable)
+            }
+            Node::ListRule(list) => (self.SymbolFirst(&list.Field.Rule.Token.Literal).0, true),
+            Node::Repeat(repeat) => {
+                let (first, nullable) = self.SymbolFirst(&repeat.Field.Rule.Token.Literal);
+                (first, nullable || repeat.MayBeEmpty())
+            }
+            Node::Optional(optional) => (self.SequenceFirst(&optional.Rule.Elements).0, true),
+            Node::Precedence(precedence) => {
+                let (mut first, nullable) = self.SymbolFirst(&precedence.Operand.Token.Literal);
+                first.extend(self.Operators(precedence, true));
+                (first, nullable)
+            }
+            Node::Predicate(_) => (TokenSet::new(), true),
+        }
+    }
+
+    // Tokens of first satisfying the predicates, and the tokens `!X` rules out when first is the wildcard.
+    pub fn Narrow(&self, mut first: TokenSet, predicates: &[&Predicate]) -> (TokenSet, TokenSet) {
+        let mut excluded = TokenSet::new();
+        for predicate in predicates {
+            let tokens = self.SymbolFirst(&predicate.Rule.Token.Literal).0;
+            match predicate.IsNot() {
+                true => excluded.extend(tokens),
+                false => first = Overlap(&first, &tokens),
+            }
+        }
+        if excluded.contains(WILDCARD) {
+            return (TokenSet::new(), TokenSet::new());
+        }
+        if !first.contains(WILDCARD) {
+            first = first.difference(&excluded).cloned().collect();
+            excluded.clear();
+        }
+        (first, excluded)
+    }
+
+    // Narrow for a token looked ahead by a pattern, which may be any token without a name.
+    pub fn AheadFirst(&self, token: &AheadToken) -> (TokenSet, TokenSet) {
+        let first = match token.Name {
+            Some(name) => self.SymbolFirst(&name.Token.Literal).0,
+            None => TokenSet::from([WILDCARD.to_string()]),
+        };
+        self.Narrow(first, &token.Predicates)
+    }
+
+    // Tokens of the prefix or the binary operators of a precedence rule.
+    pub fn Operators(&self, precedence: &PrecedenceRule, prefix: bool) -> TokenSet {
+        precedence.Levels.Elements.iter()
+            .filter(|level| level.IsPrefix() == prefix)
+            .flat_map(|level| &level.Operators)
+            .flat_map(|operator| self.SymbolFirst(&operator.Token.Literal).0)
+            .collect()
+    }
+
+    pub fn SequenceFirst(&self, nodes: &[Node]) -> (TokenSet, bool) {
+        let mut first = TokenSet::new();
+        let mut predicates: Vec<&Predicate> = vec![];
+        for node in nodes {
+            if let Node::Predicate(predicate) = node {
+                predicates.push(predicate);
+                continue;
+            }
+            let (node_first, nullable) = self.NodeFirst(node);
+            first.extend(self.Narrow(node_first, &predicates).0);
+            if !nullable {
+                return (first, false);
+            }
+        }
+        (first, true)
+    }
+
+    fn SequenceFollow(&mut self, nodes: &[Node], tail: &TokenSet) -> bool {
+        let mut changed = false;
+        for (index, node) in nodes.iter().enumerate() {
+            let (mut follow, nullable) = self.SequenceFirst(&nodes[index + 1..]);
+            if nullable {
+                follow.extend(tail.iter().cloned());
+            }
+            changed |= self.NodeFollow(node, &follow);
+        }
+        changed
+    }
+
+    fn NodeFollow(&mut self, node: &Node, follow: &TokenSet) -> bool {
+        match node {
+            Node::None => false,
+            Node::Ident(ident) => self.AddFollow(&ident.Token.Literal, follow),
+            Node::Field(field) => self.AddFollow(&field.Rule.Token.Literal, follow),
+            Node::Match(branch) => {
+                let mut changed = false;
+                for pattern in &branch.Patterns.Elements {
+                    changed |= self.SequenceFollow(&pattern.Rule.Elements, follow);
+                }
+                changed
+            }
+            Node::ListRule(list) => {
+                // Each element is followed by either the delimiter or the terminator.
+                let mut after = self.SymbolFirst(&list.Delimiter.Token.Literal).0;
+                after.extend(self.SymbolFirst(&list.Term.Token.Literal).0);
+                self.AddFollow(&list.Field.Rule.Token.Literal, &after)
+            }
+            Node::Repeat(repeat) => {
+                // Each element is followed by the delimiter, or the next element without one,
+                // or what follows the repetition.
+                let element = &repeat.Field.Rule.Token.Literal;
+                let mut after = match &repeat.Delimiter {
+                    Optional::Some(delimiter) => self.SymbolFirst(&delimiter.Token.Literal).0,
+                    Optional::None => self.SymbolFirst(element).0,
+                };
+                after.extend(follow.iter().cloned());
+                self.AddFollow(element, &after)
+            }
+            Node::Optional(optional) => self.SequenceFollow(&optional.Rule.Elements, follow),
+            Node::Predicate(_) => false,
+            Node::Precedence(precedence) => {
+                // Each operand is followed by a binary operator or what follows the rule.
+                let mut after = self.Operators(precedence, false);
+                after.extend(follow.iter().cloned());
+                self.AddFollow(&precedence.Operand.Token.Literal, &after)
+            }
+        }
+    }
+
+    fn AddFollow(&mut self, name: &str, follow: &TokenSet) -> bool {
+        match self.Symbols.Lookup(name) {
+            Some(Symbol::Rule(index)) => {
+                let before = self.Follow[index].len();
+                self.Follow[index].extend(follow.iter().cloned());
+                self.Follow[index].len() != before
+            }
+            _ => false
+        }
+    }
+}
@@ -0,0 +1,342 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::analysis::*;
+use crate::ast::*;
+use crate::scanner::PosRange;
+
+// Instances nested deeper are taken for a template instantiating itself without end.
+const MAX_DEPTH: usize = 16;
+
+struct Instance {
+    Name: String,
+    Template: usize,
+    // Names of the arguments, instances among them already named.
+    Args: Vec<String>,
+    Depth: usize,
+}
+
+struct Expander<'a> {
+    Defs: &'a [Def],
+    Templates: HashMap<&'a str, usize>,
+    // Names taken by definitions, and by instances with the template and arguments they stand for.
+    Names: HashMap<String, String>,
+    Instances: Vec<Instance>,
+    Errors: Vec<ResolveError>,
+    // The definition or template being expanded, with its parameters bound to their arguments.
+    Def: String,
+    Params: HashMap<String, String>,
+    Depth: usize,
+}
+
+// Parameters of templates never instantiated stand for Ident, a token of every grammar.
+const PLACEHOLDER: &str = "Ident";
+
+// Expands templates, definitions with parameters such as `Parens<T> := LPAREN, ($items:T, COMMA, RPAREN), RPAREN`,
+// into a file without any for the other passes.
+//
+// Each instance, e.g. `Parens<Expr>`, becomes a definition named after the template and its arguments, `ParensExpr`,
+// following the definitions of the file in the order they are first referenced.
+// Templates are left out, see ExpandUnusedTemplates for those never instantiated.
+pub fn ExpandTemplates(file: &File) -> Result<File, Vec<ResolveError>> {
+    let (definitions, _, errors) = Expand(file);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(WithDefinitions(file, definitions))
+}
+
+// ExpandTemplates, followed by the templates never instantiated, named by themselves
+// and expanded with their parameters standing for PLACEHOLDER, and the instances in them,
+// so that the rest of their rules can still be resolved and they are reported as unreachable, see Check.
+pub fn ExpandUnusedTemplates(file: &File) -> Result<File, Vec<ResolveError>> {
+    let (mut definitions, unused, errors) = Expand(file);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    definitions.extend(unused);
+    Ok(WithDefinitions(file, definitions))
+}
+
+// Definitions and instances, the templates never instantiated with their own instances, and errors.
+fn Expand(file: &File) -> (Vec<Def>, Vec<Def>, Vec<ResolveError>) {
+    let defs = &file.Definitions.Elements;
+
+    let mut expander = Expander {
+        Defs: defs,
+        Templates: HashMap::new(),
+        Names: HashMap::new(),
+        Instances: vec![],
+        Errors: vec![],
+        Def: String::new(),
+        Params: HashMap::new(),
+        Depth: 0,
+    };
+
+    // Duplicates among definitions without parameters are left to Symbols::Resolve.
+    let mut positions: HashMap<&str, (PosRange, bool)> = HashMap::new();
+    let mut templates: Vec<usize> = vec![];
+    for (index, def) in defs.iter().enumerate() {
+        let name = &def.Name.Token.Literal;
+        let template = !def.Params.is_empty();
+        match positions.get(name.as_str()) {
+            Some((previous, previous_template)) if template || *previous_template => {
+                expander.Errors.push(ResolveError::DuplicateDefinition(DuplicateDefinitionError {
+                    Name: def.Name.Token.clone(),
+                    Previous: *previous,
+                }));
+                continue;
+            }
+            Some(_) => continue,
+            None => {}
+        }
+        positions.insert(name, (def.Name.Pos, template));
+        expander.Names.insert(name.clone(), name.clone());
+        if template {
+            expander.Templates.insert(name, index);
+            expander.Params(def);
+            templates.push(index);
+        }
+    }
+
+    let mut definitions: Vec<Def> = vec![];
+    for def in defs.iter().filter(|def| def.Params.is_empty()) {
+        expander.Params.clear();
+        expander.Depth = 0;
+        definitions.push(expander.Def(def, def.Name.Token.Literal.clone()));
+    }
+
+    // Instances found on the way are expanded in turn.
+    definitions.extend(expander.ExpandInstances(0));
+
+    // Templates never instantiated are expanded along with the instances they have.
+    let instances = expander.Instances.len();
+    let mut unused: Vec<Def> = vec![];
+    for index in templates {
+        let template = &defs[index];
+        if expander.Instances[..instances].iter().any(|instance| instance.Template == index) {
+            continue;
+        }
+        expander.Params = template.Params.iter().map(|param| (param.Token.Literal.clone(), PLACEHOLDER.to_string())).collect();
+        expander.Depth = 0;
+        unused.push(expander.Def(template, template.Name.Token.Literal.clone()));
+    }
+    unused.extend(expander.ExpandInstances(instances));
+
+    (definitions, unused, expander.Errors)
+}
+
+// The file with the definitions in place of its own.
+fn WithDefinitions(file: &File, definitions: Vec<Def>) -> File {
+    File {
+        Pos: file.Pos,
+        Start: match &file.Start {
+            Optional::Some(start) => Optional::Some(CopyName(start)),
+            Optional::None => Optional::None,
+        },
+        Imports: file.Imports.clone(),
+        Tokens: file.Tokens.iter().map(|decl| TokenDef {
+            Pos: decl.Pos,
+            Kind: decl.Kind.clone(),
+            Name: CopyName(&decl.Name),
+            Literal: decl.Literal.clone(),
+        }).collect(),
+        Definitions: List {
+            Pos: file.Definitions.Pos,
+            Elements: definitions,
+            Delimiter: file.Definitions.Delimiter.clone(),
+            Term: file.Definitions.Term.clone(),
+        },
+    }
+}
+
+// A copy of a name never substituted, e.g. of a field.
+fn CopyName(ident: &Ident) -> Ident {
+    Ident {
+        Pos: ident.Pos,
+        Token: ident.Token.clone(),
+        Args: ident.Args.iter().map(CopyName).collect(),
+    }
+}
+
+impl<'a> Expander<'a> {
+    fn Error(&mut self, token: &Token, reason: String) {
+        self.Errors.push(ResolveError::Template(TemplateError {
+            Def: self.Def.clone(),
+            Token: token.clone(),
+            Reason: reason,
+        }));
+    }
+
+    // Expands the instances from the index on, including those found on the way.
+    fn ExpandInstances(&mut self, from: usize) -> Vec<Def> {
+        let defs = self.Defs;
+        let mut definitions: Vec<Def> = vec![];
+        let mut index = from;
+        while index < self.Instances.len() {
+            let instance = &self.Instances[index];
+            let (name, template, depth) = (instance.Name.clone(), &defs[instance.Template], instance.Depth);
+            self.Params = template.Params.iter().map(|param| param.Token.Literal.clone())
+                .zip(instance.Args.iter().cloned())
+                .collect();
+            self.Depth = depth;
+            definitions.push(self.Def(template, name));
+            index += 1;
+        }
+        definitions
+    }
+
+    fn Params(&mut self, template: &Def) {
+        self.Def = template.Name.Token.Literal.clone();
+        let mut seen: HashSet<&str> = HashSet::new();
+        for param in &template.Params {
+            if !seen.insert(&param.Token.Literal) {
+                self.Error(&param.Token, format!("duplicate parameter {}", param.Token.Literal));
+            }
+        }
+    }
+
+    // A copy of the definition named name, with its parameters substituted and instances named.
+    fn Def(&mut self, def: &Def, name: String) -> Def {
+        self.Def = def.Name.Token.Literal.clone();
+        Def {
+            Pos: def.Pos,
+            Annotations: def.Annotations.iter().map(|annotation| Annotation {
+                Pos: annotation.Pos,
+                Name: CopyName(&annotation.Name),
+                Args: annotation.Args.Map(Token::clone),
+            }).collect(),
+            Name: Ident {
+                Pos: def.Name.Pos,
+                Token: Token { Literal: name, ..def.Name.Token.clone() },
+                Args: vec![],
+            },
+            Params: vec![],
+            Rule: def.Rule.Map(|node| self.Node(node)),
+        }
+    }
+
+    // The name a reference to a rule or token stands for,
+    // the argument for a parameter and the instance for a template with arguments.
+    fn Symbol(&mut self, ident: &Ident) -> Ident {
+        let literal = &ident.Token.Literal;
+        let args: Vec<Ident> = ident.Args.iter().map(|arg| self.Symbol(arg)).collect();
+
+        let name = match (self.Params.get(literal), self.Templates.get(literal.as_str())) {
+            (Some(arg), _) if args.is_empty() => arg.clone(),
+            (None, None) if args.is_empty() => literal.clone(),
+            (None, Some(&template)) => self.Instance(ident, template, &args),
+            _ => {
+                self.Error(&ident.Token, format!("{} is not a template", literal));
+                literal.clone()
+            }
+        };
+
+        Ident {
+            Pos: ident.Pos,
+            Token: Token { Pos: ident.Pos, Kind: ident.Token.Kind.clone(), Literal: name, Number: None },
+            Args: vec![],
+        }
+    }
+
+    fn Instance(&mut self, ident: &Ident, template: usize, args: &[Ident]) -> String {
+        let literal = &ident.Token.Literal;
+        let params = &self.Defs[template].Params;
+        if params.len() != args.len() {
+            let params: Vec<&str> = params.iter().map(|param| param.Token.Literal.as_str()).collect();
+            self.Error(&ident.Token, format!("{} is {}<{}>, have {} arguments", literal, literal, params.join(", "), args.len()));
+            return literal.clone();
+        }
+        for arg in args {
+            if arg.Token.Literal.starts_with('"') {
+                self.Error(&arg.Token, format!("quoted literal {} cannot be an argument, declare a token for it", arg.Token.Literal));
+                return literal.clone();
+            }
+        }
+
+        let args: Vec<String> = args.iter().map(|arg| arg.Token.Literal.clone()).collect();
+        let name = format!("{}{}", literal, args.concat());
+        let instance = format!("{}<{}>", literal, args.join(", "));
+        match self.Names.get(&name) {
+            Some(previous) if *previous == instance => {}
+            Some(previous) => {
+                let reason = format!("{} is named {} like {}", instance, name, previous);
+                self.Error(&ident.Token, reason);
+            }
+            None if self.Depth >= MAX_DEPTH => {
+                let reason = format!("instances of {} nest deeper than {}", literal, MAX_DEPTH);
+                self.Error(&ident.Token, reason);
+            }
+            None => {
+                self.Names.insert(name.clone(), instance);
+                self.Instances.push(Instance {
+                    Name: name.clone(),
+                    Template: template,
+                    Args: args,
+                    Depth: self.Depth + 1,
+                });
+            }
+        }
+        name
+    }
+
+    fn Field(&mut self, field: &Field) -> Field {
+        Field {
+            Pos: field.Pos,
+            Name: CopyName(&field.Name),
+            Rule: self.Symbol(&field.Rule),
+        }
+    }
+
+    fn Node(&mut self, node: &Node) -> Node {
+        match node {
+            Node::None => Node::None,
+            Node::Ident(ident) => Node::Ident(Box::new(self.Symbol(ident))),
+            Node::Field(field) => Node::Field(Box::new(self.Field(field))),
+            Node::Match(branch) => Node::Match(Box::new(Branch {
+                Pos: branch.Pos,
+                Patterns: branch.Patterns.Map(|pattern| Pattern {
+                    Pos: pattern.Pos,
+                    Ahead: pattern.Ahead.Map(|node| self.Node(node)),
+                    Rule: pattern.Rule.Map(|node| self.Node(node)),
+                }),
+            })),
+            Node::ListRule(list) => Node::ListRule(Box::new(ListRule {
+                Pos: list.Pos,
+                Field: self.Field(&list.Field),
+                Delimiter: self.Symbol(&list.Delimiter),
+                Term: self.Symbol(&list.Term),
+            })),
+            Node::Repeat(repeat) => Node::Repeat(Box::new(RepeatRule {
+                Pos: repeat.Pos,
+                Field: self.Field(&repeat.Field),
+                Op: repeat.Op.clone(),
+                Delimiter: match &repeat.Delimiter {
+                    Optional::Some(delimiter) => Optional::Some(self.Symbol(delimiter)),
+                    Optional::None => Optional::None,
+                },
+            })),
+            Node::Optional(optional) => Node::Optional(Box::new(OptionalRule {
+                Pos: optional.Pos,
+                Rule: optional.Rule.Map(|node| self.Node(node)),
+            })),
+            Node::Precedence(precedence) => Node::Precedence(Box::new(PrecedenceRule {
+                Pos: precedence.Pos,
+                Operand: self.Symbol(&precedence.Operand),
+                Levels: precedence.Levels.Map(|level| PrecedenceLevel {
+                    Pos: level.Pos,
+                    Assoc: CopyName(&level.Assoc),
+                    Operators: level.Operators.iter().map(|operator| self.Symbol(operator)).collect(),
+                }),
+            })),
+            Node::Predicate(predicate) => Node::Predicate(Box::new(Predicate {
+                Pos: predicate.Pos,
+                Op: predicate.Op.clone(),
+                Rule: self.Symbol(&predicate.Rule),
+            })),
+        }
+    }
+}
@@ -7,18 +7,22 @@ use std::fmt;
 use crate::ast::*;
 use crate::scanner::*;
 
+#[macro_export]
 macro_rules! def_ast {
     (
         $(
+        $(#[$meta:meta])*
         $ast:ident {
-            $($name:ident: $typ:ty), *,
+            $($name:ident: $typ:ty), * $(,)?
         }
         ), *
     ) => {
         $(
+        $(#[$meta])*
         #[derive(Default)]
+        #[allow(non_snake_case)]
         pub struct $ast {
-            pub Pos: PosRange,
+            pub Pos: $crate::scanner::PosRange,
             $(
             pub $name: $typ,
             )*
@@ -69,6 +73,18 @@ pub struct List<T> {
     pub Term: TokenKind,
 }
 
+impl<T> List<T> {
+    // The list with each element mapped by f.
+    pub fn Map<U>(&self, f: impl FnMut(&T) -> U) -> List<U> {
+        List {
+            Pos: self.Pos,
+            Elements: self.Elements.iter().map(f).collect(),
+            Delimiter: self.Delimiter.clone(),
+            Term: self.Term.clone(),
+        }
+    }
+}
+
 impl<T> fmt::Display for List<T> where T: fmt::Display {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for e in &self.Elements {
@@ -84,10 +100,83 @@ pub enum Node {
     Field(Box<Field>),
     Match(Box<Branch>),
     ListRule(Box<ListRule>),
+    Repeat(Box<RepeatRule>),
+    Optional(Box<OptionalRule>),
+    Precedence(Box<PrecedenceRule>),
+    Predicate(Box<Predicate>),
 }
 
 impl Default for Node { fn default() -> Self { Node::None } }
 
+impl Def {
+    pub fn Annotation(&self, name: &str) -> Option<&Annotation> {
+        self.Annotations.iter().find(|annotation| annotation.Name.Token.Literal == name)
+    }
+}
+
+impl Annotation {
+    // The text of `@doc("...")` or `@name("...")`.
+    pub fn Text(&self) -> Option<&str> {
+        match &self.Args.Elements[..] {
+            [arg] if matches!(arg.Kind, TokenKind::String) => Some(&arg.Literal),
+            _ => None
+        }
+    }
+}
+
+impl Predicate {
+    pub fn IsNot(&self) -> bool { matches!(self.Op.Kind, TokenKind::NOT) }
+}
+
+// A token looked ahead by a pattern, with the name it must match, if any, and the predicates it must satisfy.
+pub struct AheadToken<'a> {
+    pub Name: Option<&'a Ident>,
+    pub Predicates: Vec<&'a Predicate>,
+}
+
+impl Pattern {
+    // Tokens the pattern looks ahead, one per name and one after the last name for the predicates following it.
+    pub fn AheadTokens(&self) -> Vec<AheadToken<'_>> {
+        let mut tokens: Vec<AheadToken> = vec![];
+        let mut predicates: Vec<&Predicate> = vec![];
+        for node in &self.Ahead.Elements {
+            match node {
+                Node::Ident(name) => tokens.push(AheadToken { Name: Some(name), Predicates: std::mem::take(&mut predicates) }),
+                Node::Predicate(predicate) => predicates.push(predicate),
+                _ => {}
+            }
+        }
+        if !predicates.is_empty() {
+            tokens.push(AheadToken { Name: None, Predicates: predicates });
+        }
+        tokens
+    }
+
+    // `_ => ...`, taken when no other pattern of the branch matches.
+    pub fn IsDefault(&self) -> bool {
+        matches!(&self.Ahead.Elements[..], [Node::Ident(name)] if name.Token.Literal == "_")
+    }
+
+    // The first name or predicate before `=>`.
+    pub fn AheadBegin(&self) -> &Token {
+        match self.Ahead.Elements.first() {
+            Some(Node::Ident(name)) => &name.Token,
+            Some(Node::Predicate(predicate)) => &predicate.Op,
+            _ => unreachable!("patterns look ahead at least one token"),
+        }
+    }
+}
+
+impl RepeatRule {
+    pub fn MayBeEmpty(&self) -> bool { matches!(self.Op.Kind, TokenKind::STAR) }
+}
+
+impl PrecedenceLevel {
+    pub fn IsPrefix(&self) -> bool { self.Assoc.Token.Literal == "prefix" }
+
+    pub fn IsRight(&self) -> bool { self.Assoc.Token.Literal == "right" }
+}
+
 pub enum Optional<T> {
     None,
     Some(T),
@@ -109,6 +198,8 @@ impl<T> fmt::Display for Optional<T> where T: fmt::Display {
 def_ast! {
     Ident {
         Token: Token,
+        // Arguments of a template instance, e.g. `Expr` of `Parens<Expr>`.
+        Args: Vec<Ident>,
     },
 
     Field {
@@ -116,11 +207,20 @@ def_ast! {
         Rule: Ident,
     },
     
+    // `&X` or `!X`, whether the token it stands before can start X or not, consuming nothing.
+    Predicate {
+        Op: Token,
+        Rule: Ident,
+    },
+
+    // `A, B => ...` matches A at the current token and B at the one after it.
+    // Predicates check the token of the name after them, `A, !B => ...` the one after A.
     Pattern {
-        Ahead: Ident,
+        Ahead: List<Node>,
         Rule: List<Node>,
     },
     
+    // Patterns are tried in order, a default `_ => ...` must come last.
     Branch {
         Patterns: List<Pattern>,
     },
@@ -130,13 +230,62 @@ def_ast! {
         Delimiter: Ident,
         Term: Ident,
     },
+
+    // `($f:T*)` or `($f:T+, DELIM)`, repeating while the token can start T.
+    // The delimiter may also follow the last element.
+    RepeatRule {
+        Field: Field,
+        Op: Token,
+        Delimiter: Optional<Ident>,
+    },
+
+    OptionalRule {
+        Rule: List<Node>,
+    },
+
+    // `left PLUS MINUS`, `right CARET` or `prefix MINUS`.
+    PrecedenceLevel {
+        Assoc: Ident,
+        Operators: Vec<Ident>,
+    },
+
+    // `precedence Operand { left PLUS MINUS; left STAR SLASH; prefix MINUS; }`,
+    // each level binding tighter than the ones above it.
+    PrecedenceRule {
+        Operand: Ident,
+        Levels: List<PrecedenceLevel>,
+    },
     
+    // `@name` or `@name(args)` before a definition, e.g. `@doc("...")` or `@recover(SEMICOLON)`.
+    //
+    // `@inline` and `@recover` are hints that never change what is parsed: generated parsers mark the Expect
+    // of the rule #[inline] and give it a RECOVER constant for callers to skip to with Parser::SkipTo
+    // after an error, which still stops the parse. The interpreter ignores both.
+    Annotation {
+        Name: Ident,
+        Args: List<Token>,
+    },
+    
+    // Definitions with parameters, `Parens<T> := ...`, are templates, see ExpandTemplates.
     Def {
+        Annotations: Vec<Annotation>,
         Name: Ident,
+        Params: Vec<Ident>,
         Rule: List<Node>,
     },
     
+    // `token NAME "literal"` or `keyword NAME "literal"`.
+    TokenDef {
+        Kind: Token,
+        Name: Ident,
+        Literal: Token,
+    },
+    
     File {
+        Start: Optional<Ident>,
+        // Paths of `import "path";`, relative to the importing file.
+        Imports: Vec<Token>,
+        Tokens: Vec<TokenDef>,
         Definitions: List<Def>,
     }
 }
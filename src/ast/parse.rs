@@ -3,45 +3,117 @@
 // that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
 
 use err_rs::err;
-use crate::{def_parser, tag_matches, unexpected_token};
+use crate::{def_parser, unexpected_token};
 use crate::ast::*;
 use crate::parser::*;
 
 macro_rules! range {
     ($begin: expr, $parser: expr) => {
-        crate::scanner::PosRange { Begin: $begin, End: $parser.GetPos() }
+        $parser.GetRange($begin)
     };
 }
 
-impl<T> List<T> where T: AstNodeParserTrait<T> {
-    
-    // List::Expect is special.
-    // Parsing stops at the terminator, not one token after the terminator.
-    //
-    // This is designed to deal with grammars in which
-    // the terminator of the second-level list is
-    // the delimiter of the top-level list, such as:
-    // ```
-    // {
-    //   a, b, c;
-    //   x, y, z;
-    //    ^     ^ top-level list delimiter, secondary list terminator
-    //      secondary list delimiter
-    // }
-    // ^ top-level list terminator
-    // ```
-    pub fn Expect(p: &mut Parser, delimiter: TokenKind, terminator: TokenKind) -> Result<List<T>, ParserError> {
-        let begin = p.GetPos();
+// Kinds ending lines of .ppg files besides those of Parser::new,
+// e.g. `]` of an optional element, `>` of a template instance and the literal of a token declaration.
+pub const META_SEMICOLON_AFTER: &[TokenKind] = &[TokenKind::RBRACK, TokenKind::GTR, TokenKind::String];
+
+impl File {
+    // A parser over the source of a .ppg file, scanning the keywords and completing the semicolons of the meta-grammar.
+    pub fn NewParser(src: &str) -> Parser {
+        let mut p = Parser::new(src.chars().collect());
+        p.KeywordLookup = TokenKind::MetaKeywordLookup();
+        p.SemicolonAfter.extend_from_slice(META_SEMICOLON_AFTER);
+        p
+    }
+}
+
+impl Ident {
+    // A name or a quoted literal standing for the token it is scanned as, e.g. `":="` or `"fn"`.
+    // The literal stays quoted, so that it is never taken for the name of a rule or a token.
+    pub fn ExpectSymbol(p: &mut Parser) -> Result<Ident, ParserError> {
+        match p.Token.Kind {
+            TokenKind::String => {
+                let mut token = p.GetTokenAndScan()?;
+                token.Literal = format!("\"{}\"", token.Literal);
+                Ok(Ident {
+                    Pos: token.Pos,
+                    Token: token,
+                    Args: vec![],
+                })
+            }
+            _ => {
+                let begin = p.Token.Pos.Begin;
+                let mut ident = Ident::Expect(p)?;
+                if let TokenKind::LSS = p.Token.Kind {
+                    ident.Args = Ident::ExpectArgs(p, Ident::ExpectSymbol)?;
+                    ident.Pos = range![begin, p];
+                }
+                Ok(ident)
+            }
+        }
+    }
+
+    // `<A, B>` after the name of a template or of one of its instances.
+    pub fn ExpectArgs(p: &mut Parser, element: fn(&mut Parser) -> Result<Ident, ParserError>) -> Result<Vec<Ident>, ParserError> {
+        p.MatchAndScan(TokenKind::LSS)?;
+        let mut args = vec![element(p)?];
+        while let TokenKind::COMMA = p.Token.Kind {
+            p.Scan()?;
+            args.push(element(p)?);
+        }
+        // `>>` closes nested instances.
+        p.MatchPrefixAndScan(TokenKind::GTR, ">")?;
+        Ok(args)
+    }
+}
+
+impl Node {
+    // A name or a predicate before `=>`.
+    pub fn ExpectAhead(p: &mut Parser) -> Result<Node, ParserError> {
+        match p.Token.Kind {
+            TokenKind::AND | TokenKind::NOT => Ok(Node::Predicate(Box::new(Predicate::Expect(p)?))),
+            _ => Ok(Node::Ident(Box::new(Ident::ExpectSymbol(p)?))),
+        }
+    }
+}
+
+// Offset of the token after the symbol at Peek(n), skipping the arguments of an instance.
+fn SkipSymbol(p: &mut Parser, n: usize) -> Result<usize, ParserError> {
+    let mut n = n + 1;
+    let mut depth: usize = 0;
+    loop {
+        let token = p.Peek(n)?;
+        match token.Kind {
+            TokenKind::LSS => depth += 1,
+            TokenKind::GTR if depth > 0 => depth -= 1,
+            TokenKind::Operator if depth > 0 && token.Literal.chars().all(|ch| ch == '>') => {
+                depth = depth.saturating_sub(token.Literal.len());
+            }
+            TokenKind::EOF => return Ok(n),
+            _ if depth > 0 => {}
+            _ => return Ok(n)
+        }
+        n += 1;
+        if depth == 0 {
+            return Ok(n);
+        }
+    }
+}
+
+impl<T> List<T> {
+    // List::Expect with an element parser of its own, e.g. for lists of tokens.
+    pub fn ExpectWith(p: &mut Parser, delimiter: TokenKind, terminator: TokenKind, mut element: impl FnMut(&mut Parser) -> Result<T, ParserError>) -> Result<List<T>, ParserError> {
+        let begin = p.Token.Pos.Begin;
         let mut list: Vec<T> = vec![];
 
         loop {
-            if tag_matches!(&p.Token.Kind, &terminator) {
+            if p.Token.Kind.Is(&terminator) {
                 // () <- terminator
                 // (...,...,) <- terminator
                 break;
             }
-            list.push(T::Expect(p)?);
-            if tag_matches!(&p.Token.Kind, &delimiter) {
+            list.push(element(p)?);
+            if p.Token.Kind.Is(&delimiter) {
                 // (...,..., <- delimiter
                 p.Scan()?; // delimiter
             } else {
@@ -60,6 +132,58 @@ impl<T> List<T> where T: AstNodeParserTrait<T> {
     }
 }
 
+impl<T> List<T> {
+    // Elements for as long as the current token can start one, see RepeatRule.
+    // The list may be empty only when empty is true, TokenKind::None stands for no delimiter.
+    pub fn Repeat(p: &mut Parser, delimiter: TokenKind, empty: bool, starts: impl Fn(&Token) -> bool, mut element: impl FnMut(&mut Parser) -> Result<T, ParserError>) -> Result<List<T>, ParserError> {
+        let begin = p.Token.Pos.Begin;
+        let mut list: Vec<T> = vec![];
+
+        if !empty || starts(&p.Token) {
+            loop {
+                list.push(element(p)?);
+                match delimiter {
+                    TokenKind::None => {}
+                    _ if p.Token.Kind.Is(&delimiter) => { p.Scan()?; } // delimiter, possibly trailing
+                    _ => break
+                }
+                if !starts(&p.Token) {
+                    break;
+                }
+            }
+        }
+
+        Ok(List {
+            Pos: range![begin, p],
+            Elements: list,
+            Delimiter: delimiter,
+            Term: TokenKind::None,
+        })
+    }
+}
+
+impl<T> List<T> where T: AstNodeParserTrait<T> {
+    
+    // List::Expect is special.
+    // Parsing stops at the terminator, not one token after the terminator.
+    //
+    // This is designed to deal with grammars in which
+    // the terminator of the second-level list is
+    // the delimiter of the top-level list, such as:
+    // ```
+    // {
+    //   a, b, c;
+    //   x, y, z;
+    //    ^     ^ top-level list delimiter, secondary list terminator
+    //      secondary list delimiter
+    // }
+    // ^ top-level list terminator
+    // ```
+    pub fn Expect(p: &mut Parser, delimiter: TokenKind, terminator: TokenKind) -> Result<List<T>, ParserError> {
+        List::ExpectWith(p, delimiter, terminator, T::Expect)
+    }
+}
+
 def_parser! {
     Ident, p => {
         let token = p.GetTokenAndScan()?;
@@ -68,6 +192,7 @@ def_parser! {
                 Ident {
                     Pos: token.Pos,
                     Token: token.clone(),
+                    Args: vec![],
                 }
             }
             _ => {
@@ -80,11 +205,11 @@ def_parser! {
     },
 
     Field, p => {
-        let begin = p.GetPos();
+        let begin = p.Token.Pos.Begin;
         p.MatchAndScan(TokenKind::FIELD)?;
         let name = Ident::Expect(p)?;
         p.MatchAndScan(TokenKind::COLON)?;
-        let rule = Ident::Expect(p)?;
+        let rule = Ident::ExpectSymbol(p)?;
         
         Field {
             Name: name,
@@ -93,9 +218,27 @@ def_parser! {
         }
     },
     
+    Predicate, p => {
+        let begin = p.Token.Pos.Begin;
+        let op = match p.Token.Kind {
+            TokenKind::AND | TokenKind::NOT => p.GetTokenAndScan()?,
+            _ => unexpected_token!(TokenKind::NOT, p.Token.clone())
+        };
+        let rule = Ident::ExpectSymbol(p)?;
+
+        Predicate {
+            Op: op,
+            Rule: rule,
+            Pos: range![begin, p],
+        }
+    },
+
     Pattern, p => {
-        let begin = p.GetPos();
-        let ahead = Ident::Expect(p)?;
+        let begin = p.Token.Pos.Begin;
+        let ahead = List::ExpectWith(p, TokenKind::COMMA, TokenKind::ARROW, Node::ExpectAhead)?;
+        if ahead.Elements.is_empty() {
+            unexpected_token!(TokenKind::Ident, p.Token.clone());
+        }
         p.MatchAndScan(TokenKind::ARROW)?;
         let rule = List::Expect(p, TokenKind::COMMA, TokenKind::SEMICOLON)?;
         // Delimiter of Branch::Expect
@@ -108,7 +251,7 @@ def_parser! {
     },
     
     Branch, p => {
-        let begin = p.GetPos();
+        let begin = p.Token.Pos.Begin;
         p.MatchAndScan(TokenKind::LBRACE)?;
         let patterns = List::Expect(p, TokenKind::SEMICOLON, TokenKind::RBRACE)?;
         p.Scan()?;
@@ -120,13 +263,13 @@ def_parser! {
     },
     
     ListRule, p => {
-        let begin = p.GetPos();
+        let begin = p.Token.Pos.Begin;
         p.MatchAndScan(TokenKind::LPAREN)?;
         let field = Field::Expect(p)?;
         p.MatchAndScan(TokenKind::COMMA)?;
-        let delimiter = Ident::Expect(p)?;
+        let delimiter = Ident::ExpectSymbol(p)?;
         p.MatchAndScan(TokenKind::COMMA)?;
-        let term = Ident::Expect(p)?;
+        let term = Ident::ExpectSymbol(p)?;
         p.MatchAndScan(TokenKind::RPAREN)?;
         
         ListRule {
@@ -137,34 +280,189 @@ def_parser! {
         }
     },
     
+    RepeatRule, p => {
+        let begin = p.Token.Pos.Begin;
+        p.MatchAndScan(TokenKind::LPAREN)?;
+        let field = Field::Expect(p)?;
+        let op = match p.Token.Kind {
+            TokenKind::STAR | TokenKind::PLUS => p.GetTokenAndScan()?,
+            _ => unexpected_token!(TokenKind::STAR, p.Token.clone())
+        };
+        let delimiter = match p.Token.Kind {
+            TokenKind::COMMA => {
+                p.Scan()?;
+                Optional::Some(Ident::ExpectSymbol(p)?)
+            }
+            _ => Optional::None
+        };
+        p.MatchAndScan(TokenKind::RPAREN)?;
+
+        RepeatRule {
+            Field: field,
+            Op: op,
+            Delimiter: delimiter,
+            Pos: range![begin, p],
+        }
+    },
+
+    OptionalRule, p => {
+        let begin = p.Token.Pos.Begin;
+        p.MatchAndScan(TokenKind::LBRACK)?;
+        let rule = List::Expect(p, TokenKind::COMMA, TokenKind::RBRACK)?;
+        p.MatchAndScan(TokenKind::RBRACK)?;
+
+        OptionalRule {
+            Rule: rule,
+            Pos: range![begin, p],
+        }
+    },
+    
+    PrecedenceLevel, p => {
+        let begin = p.Token.Pos.Begin;
+        // left, right or prefix, checked by Symbols::Resolve
+        let assoc = Ident::Expect(p)?;
+        let mut operators: Vec<Ident> = vec![];
+        while let TokenKind::Ident | TokenKind::String = p.Token.Kind {
+            operators.push(Ident::ExpectSymbol(p)?);
+        }
+        
+        PrecedenceLevel {
+            Assoc: assoc,
+            Operators: operators,
+            Pos: range![begin, p],
+        }
+    },
+    
+    PrecedenceRule, p => {
+        let begin = p.Token.Pos.Begin;
+        p.MatchAndScan(TokenKind::PRECEDENCE)?;
+        let operand = Ident::ExpectSymbol(p)?;
+        p.MatchAndScan(TokenKind::LBRACE)?;
+        let levels = List::Expect(p, TokenKind::SEMICOLON, TokenKind::RBRACE)?;
+        p.Scan()?;
+        
+        PrecedenceRule {
+            Operand: operand,
+            Levels: levels,
+            Pos: range![begin, p],
+        }
+    },
+    
     Node, p => {
         match p.Token.Kind {
-            TokenKind::Ident => Node::Ident(Box::new(Ident::Expect(p)?)),
+            TokenKind::Ident | TokenKind::String => Node::Ident(Box::new(Ident::ExpectSymbol(p)?)),
             TokenKind::FIELD => Node::Field(Box::new(Field::Expect(p)?)),
             TokenKind::LBRACE => Node::Match(Box::new(Branch::Expect(p)?)),
-            TokenKind::LPAREN => Node::ListRule(Box::new(ListRule::Expect(p)?)),
+            // `($name:Rule*` or `($name:Rule+` after the field.
+            TokenKind::LPAREN => {
+                let n = SkipSymbol(p, 4)?;
+                match p.Peek(n)?.Kind {
+                    TokenKind::STAR | TokenKind::PLUS => Node::Repeat(Box::new(RepeatRule::Expect(p)?)),
+                    _ => Node::ListRule(Box::new(ListRule::Expect(p)?)),
+                }
+            }
+            TokenKind::LBRACK => Node::Optional(Box::new(OptionalRule::Expect(p)?)),
+            TokenKind::PRECEDENCE => Node::Precedence(Box::new(PrecedenceRule::Expect(p)?)),
+            TokenKind::AND | TokenKind::NOT => Node::Predicate(Box::new(Predicate::Expect(p)?)),
             _ => unexpected_token!(TokenKind::None, p.Token.clone())
         }
     },
     
+    Annotation, p => {
+        let begin = p.Token.Pos.Begin;
+        p.MatchAndScan(TokenKind::AT)?;
+        let name = Ident::Expect(p)?;
+        let args = match p.Token.Kind {
+            TokenKind::LPAREN => {
+                p.Scan()?;
+                let args = List::ExpectWith(p, TokenKind::COMMA, TokenKind::RPAREN, |p| p.GetTokenAndScan())?;
+                p.Scan()?;
+                args
+            }
+            _ => List::default()
+        };
+        // Semicolon completed after an annotation on a line of its own.
+        if let TokenKind::SEMICOLON = p.Token.Kind {
+            p.Scan()?;
+        }
+        
+        Annotation {
+            Name: name,
+            Args: args,
+            Pos: range![begin, p],
+        }
+    },
+    
     Def, p => {
-        let begin = p.GetPos();
+        let begin = p.Token.Pos.Begin;
+        let mut annotations: Vec<Annotation> = vec![];
+        while let TokenKind::AT = p.Token.Kind {
+            annotations.push(Annotation::Expect(p)?);
+        }
         let name = Ident::Expect(p)?;
+        let params = match p.Token.Kind {
+            TokenKind::LSS => Ident::ExpectArgs(p, Ident::Expect)?,
+            _ => vec![]
+        };
         p.MatchAndScan(TokenKind::DEFINE)?;
         let rule = List::Expect(p, TokenKind::COMMA, TokenKind::SEMICOLON)?;
         
         Def {
+            Annotations: annotations,
             Name: name,
+            Params: params,
             Rule: rule,
             Pos: range![begin, p],
         }
     },
     
+    TokenDef, p => {
+        let begin = p.Token.Pos.Begin;
+        let kind = match p.Token.Kind {
+            TokenKind::TOKEN | TokenKind::KEYWORD => p.GetTokenAndScan()?,
+            _ => unexpected_token!(TokenKind::TOKEN, p.Token.clone())
+        };
+        let name = Ident::Expect(p)?;
+        p.Match(TokenKind::String)?;
+        let literal = p.GetTokenAndScan()?;
+        
+        TokenDef {
+            Kind: kind,
+            Name: name,
+            Literal: literal,
+            Pos: range![begin, p],
+        }
+    },
+    
     File, p => {
-        let begin = p.GetPos();
+        let begin = p.Token.Pos.Begin;
+        let start = match p.Token.Kind {
+            TokenKind::START => {
+                p.Scan()?;
+                let name = Ident::Expect(p)?;
+                p.MatchAndScan(TokenKind::SEMICOLON)?;
+                Optional::Some(name)
+            }
+            _ => Optional::None
+        };
+        let mut imports: Vec<Token> = vec![];
+        while let TokenKind::IMPORT = p.Token.Kind {
+            p.Scan()?;
+            p.Match(TokenKind::String)?;
+            imports.push(p.GetTokenAndScan()?);
+            p.MatchAndScan(TokenKind::SEMICOLON)?;
+        }
+        let mut tokens: Vec<TokenDef> = vec![];
+        while let TokenKind::TOKEN | TokenKind::KEYWORD = p.Token.Kind {
+            tokens.push(TokenDef::Expect(p)?);
+            p.MatchAndScan(TokenKind::SEMICOLON)?;
+        }
         let definitions = List::Expect(p, TokenKind::SEMICOLON, TokenKind::EOF)?;
         
         File {
+            Start: start,
+            Imports: imports,
+            Tokens: tokens,
             Definitions: definitions,
             Pos: range![begin, p],
         }
@@ -6,14 +6,14 @@ use std::fs;
 
 use embed_rs::embed_as_string;
 
-use crate::ast::{Def, List, TokenKind};
+use crate::ast::{File, TokenKind};
 use crate::parser::{AstNodeParserTrait, Parser};
 
 #[test]
 fn TestParser_Lex() {
     let src = fs::read_to_string("proc-parser.ppg").unwrap();
 
-    let mut p = Parser::new(String::from(src).chars().collect());
+    let mut p = File::NewParser(&src);
 
     loop {
         let token = p.Scan().unwrap();
@@ -28,9 +28,9 @@ fn TestParser_Lex() {
 fn TestParser_Expect() {
     let src = fs::read_to_string("proc-parser.ppg").unwrap();
 
-    let mut p = Parser::new(String::from(src).chars().collect());
+    let mut p = File::NewParser(&src);
 
     p.Scan().unwrap();
 
-    List::<Def>::Expect(&mut p, TokenKind::SEMICOLON, TokenKind::EOF).unwrap();
+    File::Expect(&mut p).unwrap();
 }
@@ -7,7 +7,7 @@ use std::fmt::{Display, format, Formatter};
 use crate::scanner::*;
 
 macro_rules! def_tokens {
-    ($typ_name:ident => { $($name:ident $literal:expr), * }) => {
+    ($typ_name:ident => { $($name:ident $literal:expr), * } $meta:ident => { $($keyword:ident $keyword_literal:expr), * }) => {
         #[derive(Clone, Debug)]
         pub enum $typ_name {
             None,
@@ -18,7 +18,12 @@ macro_rules! def_tokens {
             Float,
             String,
             Char,
+            // Token declared by a grammar, named by a literal of generated code so that it can be matched on.
+            Declared(&'static str),
+            // Token declared by a grammar loaded at run time, see TokenKind::Declare.
+            Named(std::sync::Arc<str>),
             $($name,)*
+            $($keyword,)*
         }
 
         impl Default for $typ_name {
@@ -36,14 +41,70 @@ macro_rules! def_tokens {
                     $typ_name::Float => "float",
                     $typ_name::String => "string",
                     $typ_name::Char => "char",
+                    $typ_name::Declared(name) => name,
+                    $typ_name::Named(name) => name,
                     $(
                     $typ_name::$name => concat!("\"", $literal, "\""),
                     )*
+                    $(
+                    $typ_name::$keyword => concat!("\"", $keyword_literal, "\""),
+                    )*
                 })
             }
         }
 
         impl $typ_name {
+            // Name of the kind as written in grammar rules, e.g. `SEMICOLON` or `Ident`.
+            pub fn Name(&self) -> &str {
+                match self {
+                    $typ_name::None => "None",
+                    $typ_name::EOF => "EOF",
+                    $typ_name::Ident => "Ident",
+                    $typ_name::Operator => "Operator",
+                    $typ_name::Int(_) => "Int",
+                    $typ_name::Float => "Float",
+                    $typ_name::String => "String",
+                    $typ_name::Char => "Char",
+                    $typ_name::Declared(name) => name,
+                    $typ_name::Named(name) => name,
+                    $(
+                    $typ_name::$name => stringify!($name),
+                    )*
+                    $(
+                    $typ_name::$keyword => stringify!($keyword),
+                    )*
+                }
+            }
+
+            pub fn FromName(name: &str) -> Option<$typ_name> {
+                Some(match name {
+                    "EOF" => $typ_name::EOF,
+                    "Ident" => $typ_name::Ident,
+                    "Operator" => $typ_name::Operator,
+                    "Int" => $typ_name::Int(crate::scanner::IntFormat::DEC),
+                    "Float" => $typ_name::Float,
+                    "String" => $typ_name::String,
+                    "Char" => $typ_name::Char,
+                    $(
+                    stringify!($name) => $typ_name::$name,
+                    )*
+                    $(
+                    stringify!($keyword) => $typ_name::$keyword,
+                    )*
+                    _ => return None
+                })
+            }
+
+            // Whether both are the same kind, regardless of the format of integers,
+            // declared tokens are the same by name.
+            pub fn Is(&self, other: &$typ_name) -> bool {
+                match (self, other) {
+                    ($typ_name::Declared(_) | $typ_name::Named(_), $typ_name::Declared(_) | $typ_name::Named(_)) => self.Name() == other.Name(),
+                    _ => std::mem::discriminant(self) == std::mem::discriminant(other)
+                }
+            }
+
+            // Builtin punctuation, the default Parser.KeywordLookup.
             pub fn KeywordLookup() -> std::collections::HashMap<String, $typ_name> {
                 std::collections::HashMap::from([
                     $(
@@ -51,6 +112,27 @@ macro_rules! def_tokens {
                     )*
                 ])
             }
+
+            // Literal of a keyword of the meta-grammar, e.g. `start` of START.
+            pub fn Keyword(&self) -> Option<&'static str> {
+                match self {
+                    $(
+                    $typ_name::$keyword => Some($keyword_literal),
+                    )*
+                    _ => None
+                }
+            }
+
+            // Builtin punctuation and keywords, the Parser.KeywordLookup for .ppg files.
+            //
+            // Other grammars only scan the keywords they name as keywords, see Symbols::KeywordLookup.
+            pub fn $meta() -> std::collections::HashMap<String, $typ_name> {
+                let mut lookup = $typ_name::KeywordLookup();
+                $(
+                lookup.insert($keyword_literal.to_string(), $typ_name::$keyword);
+                )*
+                lookup
+            }
         }
     };
 }
@@ -72,9 +154,23 @@ def_tokens! {
         SEMICOLON   ";",
         COMMA       ",",
         DOT         ".",
+        STAR        "*",
+        PLUS        "+",
+        AT          "@",
+        AND         "&",
+        NOT         "!",
+        LSS         "<",
+        GTR         ">",
         
         NEWLINE     "\n"
     }
+    MetaKeywordLookup => {
+        START       "start",
+        TOKEN       "token",
+        KEYWORD     "keyword",
+        IMPORT      "import",
+        PRECEDENCE  "precedence"
+    }
 }
 
 #[derive(Clone, Default)]
@@ -82,4 +178,19 @@ pub struct Token {
     pub Pos: PosRange,
     pub Kind: TokenKind,
     pub Literal: String,
+    // Decoded value of Int and Float tokens.
+    pub Number: Option<Number>,
+}
+
+impl TokenKind {
+    // Kind of a token declared by a grammar with `token NAME "literal";` or `keyword NAME "literal";`.
+    //
+    // Names of builtin kinds, e.g. `SEMICOLON`, keep their builtin kind.
+    // Other names are owned by the kind, generated code names them by `TokenKind::Declared("NAME")` instead.
+    pub fn Declare(name: &str) -> TokenKind {
+        match TokenKind::FromName(name) {
+            Some(kind) => kind,
+            None => TokenKind::Named(name.into())
+        }
+    }
 }
@@ -0,0 +1,242 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+#![allow(nonstandard_style)]
+
+use std::{env, fs, io};
+use std::io::Read;
+use std::path::Path;
+use std::process::ExitCode;
+
+use proc_parser::analysis::*;
+use proc_parser::ast::{File, TokenKind};
+use proc_parser::build::Loader;
+use proc_parser::codegen::Generator;
+use proc_parser::interpreter::{Grammar, GrammarError};
+use proc_parser::parser::Parser;
+
+const USAGE: &str = "usage: ppg <command> [arguments]
+
+commands:
+    check <grammar>                     report errors and warnings of a grammar
+    gen <grammar> [-o <out>] [--crate <path>] [--actions]
+                                        print the Rust parser generated from a grammar,
+                                        with an Actions trait building values if asked
+    lex [-g <grammar>] <input>          print the tokens of an input,
+                                        scanned as the grammar declares them
+    parse <grammar> <input>             parse an input with a grammar and print its tree
+
+<input> may be `-` to read stdin.
+
+exit status:
+    0   success, warnings may have been printed
+    1   the grammar or the input has errors
+    2   bad usage or the files cannot be read or written";
+
+// Failure of a command, mapped to the exit status.
+enum Failure {
+    // Diagnostics were printed.
+    Reported,
+    Usage(String),
+    Io(String, io::Error),
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = match args.first().map(String::as_str) {
+        Some("check") => RunCheck(&args[1..]),
+        Some("gen") => RunGen(&args[1..]),
+        Some("lex") => RunLex(&args[1..]),
+        Some("parse") => RunParse(&args[1..]),
+        Some("help" | "-h" | "--help") => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => Err(Failure::Usage(format!("unknown command \"{}\"", command))),
+        None => Err(Failure::Usage("no command".to_string())),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Reported) => ExitCode::from(1),
+        Err(Failure::Usage(message)) => {
+            eprintln!("ppg: {}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+        Err(Failure::Io(path, e)) => {
+            eprintln!("ppg: {}: {}", path, e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn ReadInput(path: &str) -> Result<String, Failure> {
+    if path == "-" {
+        let mut src = String::new();
+        return match io::stdin().read_to_string(&mut src) {
+            Ok(_) => Ok(src),
+            Err(e) => Err(Failure::Io("<stdin>".to_string(), e)),
+        };
+    }
+    fs::read_to_string(path).map_err(|e| Failure::Io(path.to_string(), e))
+}
+
+fn Print(paths: &[String], diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        eprintln!("{}", diagnostic.FormatIn(paths));
+    }
+}
+
+// Parses and checks a grammar with its imports, printing its diagnostics.
+fn Load(path: &str) -> Result<(File, Vec<String>), Failure> {
+    let src = ReadInput(path)?;
+    let mut loader = Loader::new();
+    let file = match loader.Load(Path::new(path), &src) {
+        Ok(file) => file,
+        Err(diagnostics) => {
+            Print(&loader.Paths, &diagnostics);
+            return Err(Failure::Reported);
+        }
+    };
+
+    let diagnostics = Check(&file);
+    Print(&loader.Paths, &diagnostics);
+    if diagnostics.iter().any(Diagnostic::IsError) {
+        return Err(Failure::Reported);
+    }
+    Ok((file, loader.Paths))
+}
+
+// Loads a grammar to scan and parse input with, as the interpreter does.
+fn LoadGrammar(path: &str) -> Result<Grammar, Failure> {
+    let (file, _) = Load(path)?;
+    match Grammar::new(file) {
+        Ok(grammar) => Ok(grammar),
+        Err(GrammarError::Empty) => {
+            eprintln!("{}: error: no rule to start parsing from", path);
+            Err(Failure::Reported)
+        }
+        // Reported by Load already.
+        Err(_) => Err(Failure::Reported),
+    }
+}
+
+fn RunCheck(args: &[String]) -> Result<(), Failure> {
+    match args {
+        [grammar] => Load(grammar).map(|_| ()),
+        _ => Err(Failure::Usage("check takes one grammar".to_string())),
+    }
+}
+
+fn RunGen(args: &[String]) -> Result<(), Failure> {
+    let mut grammar: Option<&String> = None;
+    let mut out: Option<&String> = None;
+    let mut generator = Generator::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" => match args.next() {
+                Some(path) => out = Some(path),
+                None => return Err(Failure::Usage("-o takes a file".to_string())),
+            },
+            "--crate" => match args.next() {
+                Some(path) => generator.CratePath = path.clone(),
+                None => return Err(Failure::Usage("--crate takes a path".to_string())),
+            },
+            "--actions" => generator.Actions = true,
+            _ if grammar.is_none() => grammar = Some(arg),
+            _ => return Err(Failure::Usage(format!("unexpected argument \"{}\"", arg))),
+        }
+    }
+    let grammar = match grammar {
+        Some(grammar) => grammar,
+        None => return Err(Failure::Usage("gen takes one grammar".to_string())),
+    };
+
+    let (file, paths) = Load(grammar)?;
+    let code = match generator.Generate(&file) {
+        Ok(code) => code,
+        Err(e) => {
+            Print(&paths, &e.Diagnostics());
+            return Err(Failure::Reported);
+        }
+    };
+
+    match out {
+        Some(out) => fs::write(out, code).map_err(|e| Failure::Io(out.clone(), e)),
+        None => {
+            print!("{}", code);
+            Ok(())
+        }
+    }
+}
+
+// Same output as TestParser_Lex, with token kinds.
+fn RunLex(args: &[String]) -> Result<(), Failure> {
+    let (grammar, input) = match args {
+        [input] => (None, input),
+        [flag, grammar, input] if flag == "-g" => (Some(grammar), input),
+        _ => return Err(Failure::Usage("lex takes one input".to_string())),
+    };
+
+    let src = ReadInput(input)?;
+    let mut p = match grammar {
+        // Scanned exactly as parse scans it.
+        Some(grammar) => LoadGrammar(grammar)?.NewParser(&src),
+        // Without a grammar the input is a .ppg file.
+        None => File::NewParser(&src)
+    };
+    loop {
+        let token = match p.Scan() {
+            Ok(token) => token,
+            Err(e) => {
+                Print(std::slice::from_ref(input), &[Diagnostic::from(&e)]);
+                return Err(Failure::Reported);
+            }
+        };
+        if let TokenKind::EOF = token.Kind {
+            break;
+        }
+        println!("{} {} {:?}", token.Pos, token.Kind.Name(), token.Literal);
+    }
+    Overflows(input, &p)
+}
+
+// Reports the literals out of range the scanner went on past.
+fn Overflows(input: &String, p: &Parser) -> Result<(), Failure> {
+    let diagnostics: Vec<Diagnostic> = p.Scanner.Overflows.iter().map(Diagnostic::from).collect();
+    Print(std::slice::from_ref(input), &diagnostics);
+    match diagnostics.is_empty() {
+        true => Ok(()),
+        false => Err(Failure::Reported),
+    }
+}
+
+fn RunParse(args: &[String]) -> Result<(), Failure> {
+    let (path, input) = match args {
+        [path, input] => (path, input),
+        _ => return Err(Failure::Usage("parse takes a grammar and an input".to_string())),
+    };
+
+    let grammar = LoadGrammar(path)?;
+    let mut p = grammar.NewParser(&ReadInput(input)?);
+    let tree = match p.Scan() {
+        Ok(_) => grammar.Expect(&mut p),
+        Err(e) => Err(e),
+    };
+    match tree {
+        Ok(tree) => {
+            print!("{}", tree);
+            Overflows(input, &p)
+        }
+        Err(e) => {
+            let mut diagnostics: Vec<Diagnostic> = p.Scanner.Overflows.iter().map(Diagnostic::from).collect();
+            diagnostics.push(Diagnostic::from(&e));
+            Print(std::slice::from_ref(input), &diagnostics);
+            Err(Failure::Reported)
+        }
+    }
+}
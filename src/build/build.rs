@@ -0,0 +1,131 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::{env, fmt, fs, io};
+use std::fmt::Formatter;
+use std::path::{Path, PathBuf};
+
+use err_rs::*;
+
+use crate::analysis::*;
+use crate::ast::File;
+use crate::build::Loader;
+use crate::codegen::Generator;
+use crate::parser::{AstNodeParserTrait, Parser};
+
+pub struct GrammarError {
+    // The grammar compiled and the files it imports.
+    pub Paths: Vec<String>,
+    pub Diagnostics: Vec<Diagnostic>,
+}
+
+impl fmt::Debug for GrammarError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self.Diagnostics.iter().map(|d| d.FormatIn(&self.Paths)).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+#[derive(Debug)]
+pub enum BuildError {
+    Io(io::Error),
+    Grammar(GrammarError),
+}
+
+// Compiles .ppg grammars from a build script:
+//
+//     fn main() {
+//         proc_parser::build::Compile("src/grammar.ppg").unwrap();
+//     }
+//
+// and in the crate:
+//
+//     include!(concat!(env!("OUT_DIR"), "/grammar.rs"));
+pub struct Build {
+    pub Generator: Generator,
+    // Directory receiving the generated code, OUT_DIR of the build script by default.
+    pub OutDir: PathBuf,
+}
+
+impl Build {
+    pub fn new() -> Build {
+        Build {
+            Generator: Generator::new(),
+            OutDir: env::var_os("OUT_DIR").map(PathBuf::from).unwrap_or_default(),
+        }
+    }
+
+    // Checks the grammar and writes the generated parser to OutDir/<name>.rs, returning its path.
+    //
+    // Diagnostics are printed as cargo warnings, warnings alone do not fail the build.
+    pub fn Compile(&self, grammar: impl AsRef<Path>) -> Result<PathBuf, BuildError> {
+        let grammar = grammar.as_ref();
+
+        // Printed first so that fixing a broken grammar reruns the build script.
+        println!("cargo:rerun-if-changed={}", grammar.display());
+
+        let src = wrap_result!(BuildError::Io, fs::read_to_string(grammar));
+
+        let mut loader = Loader::new();
+        let loaded = loader.Load(grammar, &src);
+        for path in &loader.Paths[1..] {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+        let file = match loaded {
+            Ok(file) => file,
+            Err(diagnostics) => return Fail(loader.Paths, diagnostics),
+        };
+
+        let diagnostics = Check(&file);
+        if diagnostics.iter().any(Diagnostic::IsError) {
+            return Fail(loader.Paths, diagnostics);
+        }
+        Report(&loader.Paths, &diagnostics);
+
+        let code = match self.Generator.Generate(&file) {
+            Ok(code) => code,
+            Err(e) => return Fail(loader.Paths, e.Diagnostics()),
+        };
+
+        let name = grammar.file_stem().unwrap_or_default().to_string_lossy();
+        let out = self.OutDir.join(format!("{}.rs", name));
+        wrap_result!(BuildError::Io, fs::write(&out, code));
+
+        Ok(out)
+    }
+}
+
+// Build::new().Compile(grammar)
+pub fn Compile(grammar: impl AsRef<Path>) -> Result<PathBuf, BuildError> {
+    Build::new().Compile(grammar)
+}
+
+// Parses grammar source, the first syntax error stops it.
+//
+// Imports are left to Loader.
+pub fn Parse(src: &str) -> Result<File, Diagnostic> {
+    let mut p = File::NewParser(src);
+    if let Err(e) = p.Scan() {
+        return Err(Diagnostic::from(&e));
+    }
+    let file = File::Expect(&mut p).map_err(|e| Diagnostic::from(&e))?;
+    match p.Scanner.Overflows.first() {
+        Some(e) => Err(Diagnostic::from(e)),
+        None => Ok(file)
+    }
+}
+
+fn Report(paths: &[String], diagnostics: &[Diagnostic]) {
+    for diagnostic in diagnostics {
+        println!("cargo:warning={}", diagnostic.FormatIn(paths));
+    }
+}
+
+fn Fail(paths: Vec<String>, diagnostics: Vec<Diagnostic>) -> Result<PathBuf, BuildError> {
+    Report(&paths, &diagnostics);
+    Err(BuildError::Grammar(GrammarError {
+        Paths: paths,
+        Diagnostics: diagnostics,
+    }))
+}
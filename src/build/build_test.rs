@@ -0,0 +1,53 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::{env, fs};
+use std::path::PathBuf;
+
+use crate::analysis::Severity;
+use crate::build::*;
+
+fn dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn TestBuild_Compile() {
+    let mut build = Build::new();
+    build.OutDir = dir("proc-parser-build-compile");
+
+    let out = build.Compile("src/codegen/testdata/value.ppg").unwrap();
+
+    assert_eq!(out, build.OutDir.join("value.rs"));
+    let code = fs::read_to_string(out).unwrap();
+    assert!(code.contains("pub enum Value {"));
+}
+
+#[test]
+fn TestBuild_Errors() {
+    let dir = dir("proc-parser-build-errors");
+    let grammar = dir.join("bad.ppg");
+    fs::write(&grammar, "File := $a:A, EOF\nA := $b:Missing\nDead := DOT\n").unwrap();
+
+    let mut build = Build::new();
+    build.OutDir = dir.clone();
+
+    let e = match build.Compile(&grammar) {
+        Err(BuildError::Grammar(e)) => e,
+        _ => panic!()
+    };
+    println!("{:?}", e);
+
+    assert!(!dir.join("bad.rs").exists());
+    assert_eq!(e.Diagnostics.len(), 1);
+    assert_eq!(e.Diagnostics[0].Severity, Severity::Error);
+    assert_eq!(e.Diagnostics[0].Format("bad.ppg"), "bad.ppg:2:9: error: undefined name \"Missing\"");
+
+    fs::write(&grammar, "File := $a:A, EOF\nA := $b:Ident\nDead := DOT\n").unwrap();
+    build.Compile(&grammar).unwrap();
+    assert!(dir.join("bad.rs").exists());
+}
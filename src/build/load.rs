@@ -0,0 +1,201 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::{fmt, fs};
+use std::collections::HashMap;
+use std::fmt::Formatter;
+use std::path::{Path, PathBuf};
+
+use crate::analysis::*;
+use crate::ast::{File, Token};
+use crate::parser::{AstNodeParserTrait, Parser};
+use crate::scanner::PosRange;
+
+pub struct ImportError {
+    pub Path: Token,
+    pub Reason: String,
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "cannot import \"{}\": {}", self.Path.Literal, self.Reason) }
+}
+
+impl fmt::Debug for ImportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Path.Pos, self) }
+}
+
+// A definition or token of an imported file named like one of another file.
+pub struct CollisionError {
+    pub Name: Token,
+    pub Previous: PosRange,
+    pub PreviousPath: String,
+}
+
+impl fmt::Display for CollisionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "\"{}\" is already defined at {}:{}", self.Name.Literal, self.PreviousPath, self.Previous.Begin) }
+}
+
+impl fmt::Debug for CollisionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Name.Pos, self) }
+}
+
+// Loads a grammar with the grammars it imports, merged into a single File.
+//
+// Token declarations and definitions of an imported file follow those of the file importing it,
+// a file imported more than once is merged once, `start` of imported files is ignored.
+// Positions refer to their file by its index in Paths, see Diagnostic::FormatIn.
+pub struct Loader {
+    pub Paths: Vec<String>,
+    // Canonical form of Paths, recognizing a file imported again.
+    Files: Vec<PathBuf>,
+    // Files whose imports are being merged, importing one of them again is a cycle.
+    Loading: Vec<usize>,
+    // Where each merged name is defined.
+    Names: HashMap<String, PosRange>,
+}
+
+impl Loader {
+    pub fn new() -> Loader {
+        Loader {
+            Paths: vec![],
+            Files: vec![],
+            Loading: vec![],
+            Names: HashMap::new(),
+        }
+    }
+
+    // Parses the grammar read from path, imports are relative to its directory.
+    pub fn Load(&mut self, path: &Path, src: &str) -> Result<File, Vec<Diagnostic>> {
+        let canonical = fs::canonicalize(path).unwrap_or(path.to_path_buf());
+        self.LoadFrom(path.display().to_string(), canonical, path.parent().unwrap_or(Path::new("")), src)
+    }
+
+    // Parses a grammar not read from a file, e.g. the input of the ppg! macro,
+    // named in diagnostics by name, imports are relative to dir.
+    pub fn LoadSource(&mut self, name: &str, dir: &Path, src: &str) -> Result<File, Vec<Diagnostic>> {
+        // No file canonicalizes to an empty path, an import never finds it again.
+        self.LoadFrom(name.to_string(), PathBuf::new(), dir, src)
+    }
+
+    fn LoadFrom(&mut self, name: String, canonical: PathBuf, dir: &Path, src: &str) -> Result<File, Vec<Diagnostic>> {
+        let (source, mut file) = self.Parse(name, canonical, src).map_err(|diagnostic| vec![diagnostic])?;
+
+        let mut diagnostics: Vec<Diagnostic> = vec![];
+        for decl in &file.Tokens {
+            self.Names.entry(decl.Name.Token.Literal.clone()).or_insert(decl.Name.Pos);
+        }
+        for def in &file.Definitions.Elements {
+            self.Names.entry(def.Name.Token.Literal.clone()).or_insert(def.Name.Pos);
+        }
+
+        let imports = file.Imports.clone();
+        self.Merge(source, &imports, dir, &mut file, &mut diagnostics);
+
+        if !diagnostics.is_empty() {
+            return Err(diagnostics);
+        }
+        Ok(file)
+    }
+
+    fn Parse(&mut self, name: String, canonical: PathBuf, src: &str) -> Result<(usize, File), Diagnostic> {
+        let source = self.Paths.len();
+        self.Paths.push(name);
+        self.Files.push(canonical);
+
+        let mut p = File::NewParser(src);
+        p.Scanner.BufferScanner.Pos.Source = source;
+        if let Err(e) = p.Scan() {
+            return Err(Diagnostic::from(&e));
+        }
+        match (File::Expect(&mut p), p.Scanner.Overflows.first()) {
+            (Ok(file), None) => Ok((source, file)),
+            (Ok(_), Some(e)) => Err(Diagnostic::from(e)),
+            (Err(e), _) => Err(Diagnostic::from(&e)),
+        }
+    }
+
+    fn Import(&mut self, import: &Token, reason: String, diagnostics: &mut Vec<Diagnostic>) {
+        let e = ImportError { Path: import.clone(), Reason: reason };
+        diagnostics.push(Diagnostic::new(Severity::Error, Some(import.Pos), &e));
+    }
+
+    // Whether the name is new to the merged file, reporting it otherwise.
+    fn Define(&mut self, name: &Token, diagnostics: &mut Vec<Diagnostic>) -> bool {
+        match self.Names.get(&name.Literal) {
+            // Duplicates within a file are left to Symbols::Resolve.
+            Some(previous) if previous.Begin.Source != name.Pos.Begin.Source => {
+                let e = CollisionError {
+                    Name: name.clone(),
+                    Previous: *previous,
+                    PreviousPath: self.Paths[previous.Begin.Source].clone(),
+                };
+                diagnostics.push(Diagnostic::new(Severity::Error, Some(name.Pos), &e));
+                false
+            }
+            Some(_) => true,
+            None => {
+                self.Names.insert(name.Literal.clone(), name.Pos);
+                true
+            }
+        }
+    }
+
+    // Merges the files imported by the file in dir into merged, depth first.
+    fn Merge(&mut self, source: usize, imports: &[Token], dir: &Path, merged: &mut File, diagnostics: &mut Vec<Diagnostic>) {
+        self.Loading.push(source);
+
+        for import in imports {
+            let path = dir.join(&import.Literal);
+            let canonical = match fs::canonicalize(&path) {
+                Ok(canonical) => canonical,
+                Err(e) => {
+                    self.Import(import, e.to_string(), diagnostics);
+                    continue;
+                }
+            };
+
+            match self.Files.iter().position(|file| *file == canonical) {
+                Some(index) if self.Loading.contains(&index) => {
+                    let start = self.Loading.iter().position(|loading| *loading == index).unwrap();
+                    let mut cycle: Vec<&str> = self.Loading[start..].iter().map(|loading| self.Paths[*loading].as_str()).collect();
+                    cycle.push(&self.Paths[index]);
+                    let reason = format!("import cycle {}", cycle.join(" -> "));
+                    self.Import(import, reason, diagnostics);
+                    continue;
+                }
+                Some(_) => continue,
+                None => {}
+            }
+
+            let src = match fs::read_to_string(&path) {
+                Ok(src) => src,
+                Err(e) => {
+                    self.Import(import, e.to_string(), diagnostics);
+                    continue;
+                }
+            };
+            let (index, file) = match self.Parse(path.display().to_string(), canonical, &src) {
+                Ok(parsed) => parsed,
+                Err(diagnostic) => {
+                    diagnostics.push(diagnostic);
+                    continue;
+                }
+            };
+
+            for decl in file.Tokens {
+                if self.Define(&decl.Name.Token, diagnostics) {
+                    merged.Tokens.push(decl);
+                }
+            }
+            for def in file.Definitions.Elements {
+                if self.Define(&def.Name.Token, diagnostics) {
+                    merged.Definitions.Elements.push(def);
+                }
+            }
+            self.Merge(index, &file.Imports, path.parent().unwrap_or(Path::new("")), merged, diagnostics);
+        }
+
+        self.Loading.pop();
+    }
+}
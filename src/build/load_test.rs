@@ -0,0 +1,91 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::{env, fs};
+use std::path::PathBuf;
+
+use crate::analysis::Check;
+use crate::build::*;
+
+fn dir(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for (name, src) in files {
+        fs::write(dir.join(name), src).unwrap();
+    }
+    dir
+}
+
+fn load(dir: &PathBuf, name: &str) -> (Loader, Result<crate::ast::File, Vec<crate::analysis::Diagnostic>>) {
+    let path = dir.join(name);
+    let src = fs::read_to_string(&path).unwrap();
+    let mut loader = Loader::new();
+    let file = loader.Load(&path, &src);
+    (loader, file)
+}
+
+#[test]
+fn TestLoader_Load() {
+    let dir = dir("proc-parser-load", &[
+        ("main.ppg", "start File\nimport \"expr.ppg\";\nimport \"tokens.ppg\";\n\nFile := ($stmts:Stmt, SEMICOLON, EOF)\nStmt := LET, $name:Ident, ASSIGN, $value:Expr\n"),
+        ("expr.ppg", "import \"tokens.ppg\"\n\nExpr := { Ident => $ref:Ident; Int => $int:Int; }\n"),
+        ("tokens.ppg", "token ASSIGN \":=\"\nkeyword LET \"let\"\n"),
+    ]);
+
+    let (loader, file) = load(&dir, "main.ppg");
+    let file = file.unwrap();
+
+    assert_eq!(loader.Paths.len(), 3);
+    let names: Vec<&str> = file.Definitions.Elements.iter().map(|def| def.Name.Token.Literal.as_str()).collect();
+    assert_eq!(names, vec!["File", "Stmt", "Expr"]);
+    assert_eq!(file.Tokens.len(), 2);
+    assert!(Check(&file).is_empty());
+
+    let expr = &file.Definitions.Elements[2];
+    assert!(loader.Paths[expr.Name.Pos.Begin.Source].ends_with("expr.ppg"));
+}
+
+#[test]
+fn TestLoader_Errors() {
+    let dir = dir("proc-parser-load-errors", &[
+        ("a.ppg", "import \"b.ppg\"\nimport \"missing.ppg\"\n\nFile := $b:B, EOF\nB := Ident\n"),
+        ("b.ppg", "import \"a.ppg\"\nimport \"c.ppg\"\n\nB := Int\n"),
+        ("c.ppg", "C := :=\n"),
+    ]);
+
+    let (loader, file) = load(&dir, "a.ppg");
+    let diagnostics = file.err().unwrap();
+    let lines: Vec<String> = diagnostics.iter().map(|d| d.FormatIn(&loader.Paths)).collect();
+    for line in &lines {
+        println!("{}", line);
+    }
+
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].contains("b.ppg:4:1: error: \"B\" is already defined at"));
+    assert!(lines[0].ends_with("a.ppg:5:1"));
+    assert!(lines[1].contains("b.ppg:1:8: error: cannot import \"a.ppg\": import cycle"));
+    assert!(lines[2].contains("c.ppg:1:6: error:"));
+    assert!(lines[3].contains("a.ppg:2:8: error: cannot import \"missing.ppg\""));
+}
+
+#[test]
+fn TestLoader_LoadSource() {
+    let dir = dir("proc-parser-load-source", &[
+        ("tokens.ppg", "import \"more.ppg\"\ntoken ASSIGN \":=\"\n"),
+        ("more.ppg", "keyword LET \"let\"\n"),
+    ]);
+
+    let mut loader = Loader::new();
+    let file = loader.LoadSource("inline", &dir, "import \"tokens.ppg\"\n\nFile := LET, $name:Ident, ASSIGN, $value:Int, EOF\n").unwrap();
+
+    assert_eq!(file.Tokens.len(), 2);
+    assert_eq!(loader.Paths[0], "inline");
+    assert_eq!(PathBuf::from(&loader.Paths[2]), dir.join("more.ppg"));
+
+    // The source itself is no file an import could find.
+    let mut loader = Loader::new();
+    let diagnostics = loader.LoadSource("inline", &dir, "import \"inline\"\n\nFile := EOF\n").err().unwrap();
+    assert_eq!(diagnostics[0].FormatIn(&loader.Paths).split(": error: ").next(), Some("inline:1:8"));
+}
@@ -0,0 +1,11 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+pub use build::*;
+pub use load::*;
+
+pub mod build;
+mod build_test;
+pub mod load;
+mod load_test;
@@ -0,0 +1,1014 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+use std::fmt::Formatter;
+
+use err_rs::*;
+
+use crate::analysis::*;
+use crate::ast::*;
+
+pub struct UnsupportedError {
+    pub Def: String,
+    pub Token: Token,
+    pub Reason: String,
+}
+
+impl fmt::Display for UnsupportedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "cannot generate rule {}: {}", self.Def, self.Reason) }
+}
+
+impl fmt::Debug for UnsupportedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Token.Pos, self) }
+}
+
+#[derive(Debug)]
+pub enum CodegenError {
+    Resolve(Vec<ResolveError>),
+    Unsupported(UnsupportedError),
+}
+
+impl CodegenError {
+    pub fn Diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            CodegenError::Resolve(errors) => errors.iter().map(Diagnostic::from).collect(),
+            CodegenError::Unsupported(e) => vec![Diagnostic::new(Severity::Error, Some(e.Token.Pos), e)],
+        }
+    }
+}
+
+// Emits def_ast! structs, enums and def_parser! implementations equivalent to a grammar.
+//
+// A definition consisting of a single branch becomes an enum with one variant per pattern,
+// any other definition becomes a struct with one member per field.
+// Grammars declaring their tokens also get a KeywordLookup function to scan input with,
+// grammars naming Char a CHAR_LITERALS constant for Parser.Scanner.CharLiterals.
+//
+// With Actions, an `Actions` trait is emitted as well, with an associated type per definition
+// and a method per struct, per pattern of an enum and per operand, binary and prefix operator of a precedence rule.
+// def_action! implements AstNodeActionTrait for the nodes, parsing into values returned by the methods
+// of an implementation instead of building the nodes.
+pub struct Generator {
+    // Path of this crate as seen from the generated code, `crate` when generating into this crate.
+    pub CratePath: String,
+    pub Actions: bool,
+}
+
+impl Generator {
+    pub fn new() -> Generator {
+        Generator {
+            CratePath: "proc_parser".to_string(),
+            Actions: false,
+        }
+    }
+
+    pub fn Generate(&self, file: &File) -> Result<String, CodegenError> {
+        let file = &wrap_result!(CodegenError::Resolve, ExpandTemplates(file));
+        let symbols = wrap_result!(CodegenError::Resolve, Symbols::Resolve(file));
+        let sets = GrammarSets::Compute(file, &symbols);
+
+        let mut generation = Generation {
+            Crate: &self.CratePath,
+            File: file,
+            Sets: &sets,
+            Structs: vec![],
+            Enums: vec![],
+            Parsers: vec![],
+            Acting: false,
+            Methods: vec![],
+            Actions: vec![],
+        };
+        for def in &file.Definitions.Elements {
+            generation.Def(def)?;
+        }
+        if self.Actions {
+            generation.Acting = true;
+            for def in &file.Definitions.Elements {
+                generation.Act(def)?;
+            }
+        }
+
+        Ok(generation.Finish())
+    }
+}
+
+struct Writer {
+    Out: String,
+    Indent: usize,
+}
+
+impl Writer {
+    fn new(indent: usize) -> Writer { Writer { Out: String::new(), Indent: indent } }
+
+    fn Line(&mut self, line: &str) {
+        if !line.is_empty() {
+            self.Out.push_str(&"    ".repeat(self.Indent));
+            self.Out.push_str(line);
+        }
+        self.Out.push('\n');
+    }
+
+    fn Open(&mut self, line: &str) {
+        self.Line(line);
+        self.Indent += 1;
+    }
+
+    fn Close(&mut self, line: &str) {
+        self.Indent -= 1;
+        self.Line(line);
+    }
+}
+
+// `field_name` or `SEMICOLON` to `FieldName` or `Semicolon`.
+fn PascalCase(name: &str) -> String {
+    name.split('_').filter(|part| !part.is_empty()).map(|part| {
+        let mut chars = part.chars();
+        let first = chars.next().unwrap().to_uppercase().to_string();
+        let rest: String = chars.collect();
+        let rest = if rest.chars().all(|ch| !ch.is_lowercase()) { rest.to_lowercase() } else { rest };
+        first + &rest
+    }).collect()
+}
+
+// Name of the local variable holding a field, avoiding keywords and the names used by the generated code.
+fn Local(name: &str) -> String {
+    const RESERVED: &[&str] = &[
+        "p", "a", "begin",
+        "as", "async", "await", "box", "break", "const", "continue", "crate", "do", "dyn", "else", "enum", "extern",
+        "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+        "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "unsafe", "use", "where",
+        "while", "yield",
+    ];
+    if RESERVED.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+struct FieldInfo {
+    Name: String,
+    Type: String,
+    // Declared inside a nested branch or an optional group, stored as Optional.
+    Nested: bool,
+    // Type is already a Box.
+    Boxed: bool,
+}
+
+struct Generation<'a> {
+    Crate: &'a str,
+    File: &'a File,
+    Sets: &'a GrammarSets,
+    // def_ast! entries.
+    Structs: Vec<(String, Vec<(String, String)>)>,
+    // Rendered enum declarations.
+    Enums: Vec<String>,
+    // def_parser! entries.
+    Parsers: Vec<(String, String)>,
+    // Parsing by actions instead of building nodes, see Generator.Actions.
+    Acting: bool,
+    // Methods of the Actions trait.
+    Methods: Vec<String>,
+    // def_action! entries.
+    Actions: Vec<(String, String)>,
+}
+
+impl<'a> Generation<'a> {
+    fn Unsupported(&self, def: &str, token: &Token, reason: &str) -> CodegenError {
+        CodegenError::Unsupported(UnsupportedError {
+            Def: def.to_string(),
+            Token: token.clone(),
+            Reason: reason.to_string(),
+        })
+    }
+
+    fn Lookup(&self, name: &str) -> Symbol {
+        self.Sets.Symbols.Lookup(name).expect("names are resolved by Generator::Generate")
+    }
+
+    fn DefName(&self, index: usize) -> &'a str {
+        &self.File.Definitions.Elements[index].Name.Token.Literal
+    }
+
+    fn IsEnum(&self, index: usize) -> bool {
+        matches!(&self.File.Definitions.Elements[index].Rule.Elements[..], [Node::Match(_)] | [Node::Precedence(_)])
+    }
+
+    fn Annotation(&self, def: &str, name: &str) -> Option<&'a Annotation> {
+        self.File.Definitions.Elements.iter()
+            .find(|other| other.Name.Token.Literal == def)
+            .and_then(|def| def.Annotation(name))
+    }
+
+    // Doc comment of the type generated for a definition annotated with `@doc("...")`.
+    fn Doc(&self, w: &mut Writer, def: &str) {
+        if let Some(doc) = self.Annotation(def, "doc").and_then(Annotation::Text) {
+            for line in doc.lines() {
+                w.Line(&format!("/// {}", line).trim_end());
+            }
+        }
+    }
+
+    fn TokenType(&self) -> String { format!("{}::ast::Token", self.Crate) }
+
+    fn KindExpr(&self, kind: &TokenKind) -> String {
+        match kind {
+            TokenKind::Int(_) => format!("{0}::ast::TokenKind::Int({0}::scanner::IntFormat::DEC)", self.Crate),
+            TokenKind::Declared(_) | TokenKind::Named(_) => format!("{}::ast::TokenKind::Declared({:?})", self.Crate, kind.Name()),
+            _ => format!("{}::ast::TokenKind::{}", self.Crate, kind.Name()),
+        }
+    }
+
+    fn KindPattern(&self, name: &str) -> String {
+        match name {
+            "Int" => format!("{}::ast::TokenKind::Int(_)", self.Crate),
+            _ if Unquote(name).is_some() => match self.Lookup(name) {
+                Symbol::Token(kind) => self.KindExpr(&kind),
+                _ => unreachable!("literals are tokens"),
+            },
+            _ if matches!(self.Sets.Symbols.Tokens.get(name), Some(TokenKind::Named(_))) => {
+                format!("{}::ast::TokenKind::Declared({:?})", self.Crate, name)
+            }
+            _ => format!("{}::ast::TokenKind::{}", self.Crate, name),
+        }
+    }
+
+    // A lookahead name usable in a variant name, quoted literals go by their builtin kind or their letters.
+    fn AheadName(&self, name: &str) -> String {
+        match (Unquote(name), self.Lookup(name)) {
+            (Some(literal), Symbol::Token(TokenKind::Named(_))) => literal.chars().filter(|ch| ch.is_alphanumeric() || *ch == '_').collect(),
+            (Some(_), Symbol::Token(kind)) => kind.Name().to_string(),
+            _ => name.to_string(),
+        }
+    }
+
+    // Tokens a branch looks ahead, the most any of its patterns names.
+    fn BranchLookahead(&self, branch: &Branch) -> usize {
+        branch.Patterns.Elements.iter().map(|pattern| pattern.AheadTokens().len()).max().unwrap_or(1)
+    }
+
+    // `match` dispatching a branch on the kinds of the tokens it looks ahead.
+    fn BranchMatch(&self, branch: &Branch) -> String {
+        let k = self.BranchLookahead(branch);
+        if k == 1 {
+            return "match p.Token.Kind {".to_string();
+        }
+        let kinds: Vec<String> = (0..k).map(|n| match n {
+            0 => "p.Token.Kind.clone()".to_string(),
+            _ => format!("p.Peek({})?.Kind.clone()", n),
+        }).collect();
+        format!("match ({}) {{", kinds.join(", "))
+    }
+
+    // Match arm pattern for the lookahead of a branch pattern, leaving out tokens taken by previous arms.
+    //
+    // Branches looking k > 1 tokens ahead match tuples, with `_` for positions past the pattern.
+    // Tokens `!X` rules out of any token are left to a guard.
+    fn AheadPattern(&self, pattern: &Pattern, k: usize, covered: &mut TokenSet) -> Option<String> {
+        if covered.contains(WILDCARD) {
+            return None;
+        }
+        let ahead = pattern.AheadTokens();
+        let mut positions: Vec<String> = vec![];
+        let mut guards: Vec<String> = vec![];
+        let mut taken = TokenSet::new();
+        for n in 0..k {
+            let (first, excluded) = match ahead.get(n) {
+                Some(token) => self.Sets.AheadFirst(token),
+                None => (TokenSet::from([WILDCARD.to_string()]), TokenSet::new()),
+            };
+            if !excluded.is_empty() {
+                let kind = if n == 0 { "p.Token.Kind".to_string() } else { format!("p.Peek({})?.Kind", n) };
+                let kinds: Vec<String> = excluded.iter().map(|name| self.KindPattern(name)).collect();
+                guards.push(format!("!matches!({}, {})", kind, kinds.join(" | ")));
+            }
+            if first.contains(WILDCARD) {
+                positions.push("_".to_string());
+                continue;
+            }
+            let kinds: Vec<String> = match n {
+                0 => first.difference(covered).map(|name| self.KindPattern(name)).collect(),
+                _ => first.iter().map(|name| self.KindPattern(name)).collect(),
+            };
+            if kinds.is_empty() {
+                return None;
+            }
+            if n == 0 {
+                taken = first;
+            }
+            positions.push(kinds.join(" | "));
+        }
+
+        // Only tokens matched whatever follows them are taken from later arms.
+        if ahead.len() == 1 && guards.is_empty() {
+            match positions[0].as_str() {
+                "_" => { covered.insert(WILDCARD.to_string()); }
+                _ => covered.extend(taken),
+            }
+        }
+        let arm = match k {
+            1 => positions.swap_remove(0),
+            _ => format!("({})", positions.join(", ")),
+        };
+        match guards.is_empty() {
+            true => Some(arm),
+            false => Some(format!("{} if {}", arm, guards.join(" && "))),
+        }
+    }
+
+    // Parsing the rule by the actions `a` into its value.
+    fn ActCall(&self, name: &str) -> String {
+        format!("<{} as {}::parser::AstNodeActionTrait<A>>::Act(p, a)", name, self.Crate)
+    }
+
+    // Type and expression of a value parsed by `$name:Rule`, not boxed.
+    fn Value(&self, rule: &Ident) -> (String, String) {
+        match self.Lookup(&rule.Token.Literal) {
+            Symbol::Rule(index) if self.Acting => (format!("Self::{}", self.DefName(index)), format!("{}?", self.ActCall(self.DefName(index)))),
+            Symbol::Rule(index) => (self.DefName(index).to_string(), format!("{}::Expect(p)?", self.DefName(index))),
+            Symbol::Token(kind) => (self.TokenType(), format!("{{ p.Match({})?; p.GetTokenAndScan()? }}", self.KindExpr(&kind))),
+            Symbol::Any => (self.TokenType(), "p.GetTokenAndScan()?".to_string()),
+        }
+    }
+
+    fn ListValue(&self, def: &str, list: &ListRule) -> Result<(String, String), CodegenError> {
+        let mut kinds = vec![];
+        for separator in [&list.Delimiter, &list.Term] {
+            match self.Lookup(&separator.Token.Literal) {
+                Symbol::Token(kind) => kinds.push(self.KindExpr(&kind)),
+                _ => return Err(self.Unsupported(def, &separator.Token, "list delimiter and terminator must be tokens")),
+            }
+        }
+
+        let element = &list.Field.Rule.Token.Literal;
+        Ok(match self.Lookup(element) {
+            Symbol::Rule(index) if self.Acting => (
+                format!("{}::ast::List<Self::{}>", self.Crate, self.DefName(index)),
+                format!("{}::ast::List::ExpectWith(p, {}, {}, |p| {})?", self.Crate, kinds[0], kinds[1], self.ActCall(self.DefName(index))),
+            ),
+            Symbol::Rule(index) => (
+                format!("{}::ast::List<{}>", self.Crate, self.DefName(index)),
+                format!("{}::ast::List::<{}>::Expect(p, {}, {})?", self.Crate, self.DefName(index), kinds[0], kinds[1]),
+            ),
+            Symbol::Token(kind) => (
+                format!("{}::ast::List<{}>", self.Crate, self.TokenType()),
+                format!("{}::ast::List::ExpectWith(p, {}, {}, |p| {{ p.Match({})?; p.GetTokenAndScan() }})?", self.Crate, kinds[0], kinds[1], self.KindExpr(&kind)),
+            ),
+            Symbol::Any => (
+                format!("{}::ast::List<{}>", self.Crate, self.TokenType()),
+                format!("{}::ast::List::ExpectWith(p, {}, {}, |p| p.GetTokenAndScan())?", self.Crate, kinds[0], kinds[1]),
+            ),
+        })
+    }
+
+    fn RepeatValue(&self, def: &str, repeat: &RepeatRule) -> Result<(String, String), CodegenError> {
+        let delimiter = match &repeat.Delimiter {
+            Optional::Some(delimiter) => match self.Lookup(&delimiter.Token.Literal) {
+                Symbol::Token(kind) => self.KindExpr(&kind),
+                _ => return Err(self.Unsupported(def, &delimiter.Token, "repetition delimiter must be a token")),
+            },
+            Optional::None => format!("{}::ast::TokenKind::None", self.Crate),
+        };
+
+        let element = &repeat.Field.Rule.Token.Literal;
+        let first = self.Sets.SymbolFirst(element).0;
+        let starts = if first.contains(WILDCARD) {
+            "|_| true".to_string()
+        } else {
+            format!("|token| matches!(token.Kind, {})", first.iter().map(|name| self.KindPattern(name)).collect::<Vec<String>>().join(" | "))
+        };
+
+        let (typ, parse) = match self.Lookup(element) {
+            Symbol::Rule(index) if self.Acting => (format!("Self::{}", self.DefName(index)), format!("|p| {}", self.ActCall(self.DefName(index)))),
+            Symbol::Rule(index) => (self.DefName(index).to_string(), format!("{}::Expect", self.DefName(index))),
+            Symbol::Token(kind) => (self.TokenType(), format!("|p| {{ p.Match({})?; p.GetTokenAndScan() }}", self.KindExpr(&kind))),
+            Symbol::Any => (self.TokenType(), "|p| p.GetTokenAndScan()".to_string()),
+        };
+        // `Self` of the trait does not name the values in parsers.
+        let turbofish = if self.Acting { String::new() } else { format!("::<{}>", typ) };
+        Ok((
+            format!("{}::ast::List<{}>", self.Crate, typ),
+            format!("{}::ast::List{}::Repeat(p, {}, {}, {}, {})?", self.Crate, turbofish, delimiter, repeat.MayBeEmpty(), starts, parse),
+        ))
+    }
+
+    // Type and expression stored in a struct member, structs are boxed so that recursive rules have a size.
+    // Values of actions are passed as they are.
+    fn Member(&self, def: &str, node: &Node) -> Result<Option<(String, FieldInfo, String)>, CodegenError> {
+        let (name, (typ, expr)) = match node {
+            Node::Field(field) => (&field.Name.Token.Literal, self.Value(&field.Rule)),
+            Node::ListRule(list) => (&list.Field.Name.Token.Literal, self.ListValue(def, list)?),
+            Node::Repeat(repeat) => (&repeat.Field.Name.Token.Literal, self.RepeatValue(def, repeat)?),
+            _ => return Ok(None),
+        };
+        let boxed = match node {
+            Node::Field(field) if !self.Acting => matches!(self.Lookup(&field.Rule.Token.Literal), Symbol::Rule(index) if !self.IsEnum(index)),
+            _ => false
+        };
+        let info = FieldInfo {
+            Name: name.clone(),
+            Type: if boxed { format!("Box<{}>", typ) } else { typ },
+            Nested: false,
+            Boxed: boxed,
+        };
+        let expr = if boxed { format!("Box::new({})", expr) } else { expr };
+        Ok(Some((name.clone(), info, expr)))
+    }
+
+    fn CollectFields(&self, def: &str, nodes: &[Node], nested: bool, fields: &mut Vec<FieldInfo>) -> Result<(), CodegenError> {
+        for node in nodes {
+            if let Node::Match(branch) = node {
+                for pattern in &branch.Patterns.Elements {
+                    self.CollectFields(def, &pattern.Rule.Elements, true, fields)?;
+                }
+                continue;
+            }
+            if let Node::Optional(optional) = node {
+                self.CollectFields(def, &optional.Rule.Elements, true, fields)?;
+                continue;
+            }
+            let Some((_, mut info, _)) = self.Member(def, node)? else { continue; };
+            info.Nested = nested;
+
+            let token = match node {
+                Node::Field(field) => &field.Name.Token,
+                Node::ListRule(list) => &list.Field.Name.Token,
+                Node::Repeat(repeat) => &repeat.Field.Name.Token,
+                _ => unreachable!(),
+            };
+            if PascalCase(&info.Name) == "Pos" {
+                return Err(self.Unsupported(def, token, "field name is reserved for the position"));
+            }
+            match fields.iter().find(|field| field.Name == info.Name) {
+                Some(field) if field.Type != info.Type => {
+                    return Err(self.Unsupported(def, token, "field has different types in different patterns"));
+                }
+                Some(_) => {}
+                None => fields.push(info),
+            }
+        }
+        Ok(())
+    }
+
+    fn Sequence(&self, w: &mut Writer, def: &str, nodes: &[Node], nested: bool) -> Result<(), CodegenError> {
+        for node in nodes {
+            match node {
+                Node::None => {}
+                Node::Ident(ident) => w.Line(&match self.Lookup(&ident.Token.Literal) {
+                    Symbol::Rule(index) if self.Acting => format!("{}?;", self.ActCall(self.DefName(index))),
+                    Symbol::Rule(index) => format!("{}::Expect(p)?;", self.DefName(index)),
+                    Symbol::Token(kind) => format!("p.MatchAndScan({})?;", self.KindExpr(&kind)),
+                    Symbol::Any => "p.Scan()?;".to_string(),
+                }),
+                Node::Field(_) | Node::ListRule(_) | Node::Repeat(_) => {
+                    let (name, _, expr) = self.Member(def, node)?.unwrap();
+                    if nested {
+                        w.Line(&format!("{} = {}::ast::Optional::Some({});", Local(&name), self.Crate, expr));
+                    } else {
+                        w.Line(&format!("let {} = {};", Local(&name), expr));
+                    }
+                }
+                Node::Match(branch) => {
+                    w.Open(&self.BranchMatch(branch));
+                    let k = self.BranchLookahead(branch);
+                    let mut covered = TokenSet::new();
+                    for pattern in &branch.Patterns.Elements {
+                        let Some(arm) = self.AheadPattern(pattern, k, &mut covered) else { continue; };
+                        let mut body = Writer::new(w.Indent + 1);
+                        self.Sequence(&mut body, def, &pattern.Rule.Elements, true)?;
+                        if body.Out.is_empty() {
+                            w.Line(&format!("{} => {{}}", arm));
+                        } else {
+                            w.Open(&format!("{} => {{", arm));
+                            w.Out.push_str(&body.Out);
+                            w.Close("}");
+                        }
+                    }
+                    if !covered.contains(WILDCARD) {
+                        w.Line(&format!("_ => {0}::unexpected_token!({0}::ast::TokenKind::None, p.Token.clone()),", self.Crate));
+                    }
+                    w.Close("}");
+                }
+                Node::Precedence(_) => unreachable!("precedence rules are checked by Symbols::Resolve"),
+                Node::Predicate(predicate) => {
+                    let rule = &predicate.Rule.Token.Literal;
+                    let first = self.Sets.SymbolFirst(rule).0;
+                    if first.contains(WILDCARD) {
+                        if predicate.IsNot() {
+                            return Err(self.Unsupported(def, &predicate.Op, "!_ never holds"));
+                        }
+                        continue;
+                    }
+                    let kinds: Vec<String> = first.iter().map(|name| self.KindPattern(name)).collect();
+                    let want = match (predicate.IsNot(), self.Lookup(rule)) {
+                        (false, Symbol::Token(kind)) => self.KindExpr(&kind),
+                        _ => format!("{}::ast::TokenKind::None", self.Crate),
+                    };
+                    w.Open(&format!("if {}matches!(p.Token.Kind, {}) {{", if predicate.IsNot() { "" } else { "!" }, kinds.join(" | ")));
+                    w.Line(&format!("{}::unexpected_token!({}, p.Token.clone());", self.Crate, want));
+                    w.Close("}");
+                }
+                Node::Optional(optional) => {
+                    let first = self.Sets.SequenceFirst(&optional.Rule.Elements).0;
+                    if first.is_empty() {
+                        continue;
+                    }
+                    let arm = if first.contains(WILDCARD) {
+                        "_".to_string()
+                    } else {
+                        first.iter().map(|name| self.KindPattern(name)).collect::<Vec<String>>().join(" | ")
+                    };
+                    w.Open("match p.Token.Kind {");
+                    w.Open(&format!("{} => {{", arm));
+                    self.Sequence(w, def, &optional.Rule.Elements, true)?;
+                    w.Close("}");
+                    if !first.contains(WILDCARD) {
+                        w.Line("_ => {}");
+                    }
+                    w.Close("}");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn Def(&mut self, def: &'a Def) -> Result<(), CodegenError> {
+        let name = &def.Name.Token.Literal;
+        match &def.Rule.Elements[..] {
+            [Node::Match(branch)] => self.Enum(name, branch)?,
+            [Node::Precedence(precedence)] => self.Precedence(name, precedence)?,
+            nodes => self.Struct(name, nodes)?,
+        }
+
+        // Tokens to skip to after an error, e.g. with Parser::SkipTo.
+        if let Some(recover) = def.Annotation("recover") {
+            let kinds: Vec<String> = recover.Args.Elements.iter().map(|arg| {
+                let name = match arg.Kind {
+                    TokenKind::String => format!("\"{}\"", arg.Literal),
+                    _ => arg.Literal.clone(),
+                };
+                match self.Lookup(&name) {
+                    Symbol::Token(kind) => self.KindExpr(&kind),
+                    _ => unreachable!("recovery tokens are checked by Symbols::Resolve"),
+                }
+            }).collect();
+
+            let mut e = Writer::new(0);
+            e.Open(&format!("impl {} {{", name));
+            e.Line(&format!("pub const RECOVER: &'static [{}::ast::TokenKind] = &[{}];", self.Crate, kinds.join(", ")));
+            e.Close("}");
+            self.Enums.push(e.Out);
+        }
+        Ok(())
+    }
+
+    fn Struct(&mut self, name: &str, nodes: &[Node]) -> Result<(), CodegenError> {
+        let mut fields: Vec<FieldInfo> = vec![];
+        self.CollectFields(name, nodes, false, &mut fields)?;
+
+        let mut w = Writer::new(2);
+        w.Line("let begin = p.Token.Pos.Begin;");
+        for field in fields.iter().filter(|field| field.Nested) {
+            w.Line(&format!("let mut {} = {}::ast::Optional::None;", Local(&field.Name), self.Crate));
+        }
+        self.Sequence(&mut w, name, nodes, false)?;
+        w.Line("");
+        w.Open(&format!("{} {{", name));
+        for field in &fields {
+            w.Line(&format!("{}: {},", PascalCase(&field.Name), Local(&field.Name)));
+        }
+        w.Line("Pos: p.GetRange(begin),");
+        w.Close("}");
+
+        let members = fields.iter().map(|field| {
+            let typ = if field.Nested { format!("{}::ast::Optional<{}>", self.Crate, field.Type) } else { field.Type.clone() };
+            (PascalCase(&field.Name), typ)
+        }).collect();
+        self.Structs.push((name.to_string(), members));
+        self.Parsers.push((name.to_string(), w.Out));
+        Ok(())
+    }
+
+    // Variant of an enum for a pattern, named after its only field or otherwise its lookahead.
+    fn Variant(&self, pattern: &Pattern, fields: &[FieldInfo], taken: &[&str]) -> String {
+        let ahead: Vec<String> = pattern.Ahead.Elements.iter().filter_map(|node| match node {
+            Node::Ident(name) => Some(self.AheadName(&name.Token.Literal)),
+            _ => None,
+        }).collect();
+        let mut variant = PascalCase(&match fields {
+            [field] if !field.Nested => field.Name.clone(),
+            _ => ahead.join("_"),
+        });
+        if variant.is_empty() {
+            variant = "Other".to_string(); // `_ => ...`
+        }
+        while variant == "None" || taken.contains(&variant.as_str()) {
+            variant.push('_');
+        }
+        variant
+    }
+
+    fn Enum(&mut self, name: &str, branch: &Branch) -> Result<(), CodegenError> {
+        let mut variants: Vec<(String, Option<String>)> = vec![];
+        let mut covered = TokenSet::new();
+
+        let mut w = Writer::new(2);
+        w.Open(&self.BranchMatch(branch));
+        let k = self.BranchLookahead(branch);
+        for pattern in &branch.Patterns.Elements {
+            let Some(arm) = self.AheadPattern(pattern, k, &mut covered) else { continue; };
+            let nodes = &pattern.Rule.Elements;
+
+            let mut fields: Vec<FieldInfo> = vec![];
+            self.CollectFields(name, nodes, false, &mut fields)?;
+
+            let taken: Vec<&str> = variants.iter().map(|(variant, _)| variant.as_str()).collect();
+            let variant = self.Variant(pattern, &fields, &taken);
+
+            w.Open(&format!("{} => {{", arm));
+            match &fields[..] {
+                [] => {
+                    self.Sequence(&mut w, name, nodes, false)?;
+                    w.Line(&format!("{}::{}", name, variant));
+                    variants.push((variant, None));
+                }
+                [field] if !field.Nested => {
+                    self.Sequence(&mut w, name, nodes, false)?;
+                    if field.Boxed {
+                        w.Line(&format!("{}::{}({})", name, variant, Local(&field.Name)));
+                        variants.push((variant, Some(field.Type.clone())));
+                    } else {
+                        w.Line(&format!("{}::{}(Box::new({}))", name, variant, Local(&field.Name)));
+                        variants.push((variant, Some(format!("Box<{}>", field.Type))));
+                    }
+                }
+                _ => {
+                    // Patterns with several fields get a struct of their own.
+                    let aux = format!("{}{}", name, variant);
+                    self.Struct(&aux, nodes)?;
+                    w.Line(&format!("{}::{}(Box::new({}::Expect(p)?))", name, variant, aux));
+                    variants.push((variant, Some(format!("Box<{}>", aux))));
+                }
+            }
+            w.Close("}");
+        }
+        if !covered.contains(WILDCARD) {
+            w.Line(&format!("_ => {0}::unexpected_token!({0}::ast::TokenKind::None, p.Token.clone()),", self.Crate));
+        }
+        w.Close("}");
+
+        let mut e = Writer::new(0);
+        self.Doc(&mut e, name);
+        e.Open(&format!("pub enum {} {{", name));
+        e.Line("None,");
+        for (variant, payload) in &variants {
+            match payload {
+                Some(typ) => e.Line(&format!("{}({}),", variant, typ)),
+                None => e.Line(&format!("{},", variant)),
+            }
+        }
+        e.Close("}");
+        e.Line("");
+        e.Open(&format!("impl Default for {} {{", name));
+        e.Line(&format!("fn default() -> Self {{ {}::None }}", name));
+        e.Close("}");
+
+        self.Enums.push(e.Out);
+        self.Parsers.push((name.to_string(), w.Out));
+        Ok(())
+    }
+
+    // An enum of the operand, `<Name>Binary` and `<Name>Unary` nodes, parsed by precedence climbing.
+    fn Precedence(&mut self, name: &str, precedence: &PrecedenceRule) -> Result<(), CodegenError> {
+        let levels = &precedence.Levels.Elements;
+        let prefix = levels.iter().any(|level| level.IsPrefix());
+        let binary = levels.iter().any(|level| !level.IsPrefix());
+        let (operand, _) = self.Value(&precedence.Operand);
+
+        if binary {
+            self.Structs.push((format!("{}Binary", name), vec![
+                ("Left".to_string(), name.to_string()),
+                ("Op".to_string(), self.TokenType()),
+                ("Right".to_string(), name.to_string()),
+            ]));
+        }
+        if prefix {
+            self.Structs.push((format!("{}Unary", name), vec![
+                ("Op".to_string(), self.TokenType()),
+                ("Operand".to_string(), name.to_string()),
+            ]));
+        }
+
+        let mut e = Writer::new(0);
+        self.Doc(&mut e, name);
+        e.Open(&format!("pub enum {} {{", name));
+        e.Line("None,");
+        e.Line(&format!("Operand(Box<{}>),", operand));
+        if binary {
+            e.Line(&format!("Binary(Box<{}Binary>),", name));
+        }
+        if prefix {
+            e.Line(&format!("Unary(Box<{}Unary>),", name));
+        }
+        e.Close("}");
+        e.Line("");
+        e.Open(&format!("impl Default for {} {{", name));
+        e.Line(&format!("fn default() -> Self {{ {}::None }}", name));
+        e.Close("}");
+        e.Line("");
+        self.Climb(&mut e, name, precedence);
+
+        let mut w = Writer::new(2);
+        w.Line(&format!("{}::Climb(p, 1)?", name));
+
+        self.Enums.push(e.Out);
+        self.Parsers.push((name.to_string(), w.Out));
+        Ok(())
+    }
+
+    // `Climb`, or `ClimbWith` building the values of actions, taking binary operators of at least the minimum level.
+    fn Climb(&self, e: &mut Writer, name: &str, precedence: &PrecedenceRule) {
+        let levels = &precedence.Levels.Elements;
+        let prefix = levels.iter().any(|level| level.IsPrefix());
+        let binary = levels.iter().any(|level| !level.IsPrefix());
+        let (_, expr) = self.Value(&precedence.Operand);
+        let range = "p.GetRange(begin)";
+        let kinds: Vec<String> = levels.iter()
+            .map(|level| level.Operators.iter()
+                .map(|operator| self.KindPattern(&operator.Token.Literal))
+                .collect::<Vec<String>>().join(" | "))
+            .collect();
+        let min = if binary { "min" } else { "_min" };
+        let climb = |level: &str| match self.Acting {
+            true => format!("{}::ClimbWith(p, a, {})?", name, level),
+            false => format!("{}::Climb(p, {})?", name, level),
+        };
+
+        e.Open(&format!("impl {} {{", name));
+        if self.Acting {
+            e.Line("// Climb building the values of the actions.");
+            e.Open(&format!("fn ClimbWith<A: Actions>(p: &mut {0}::parser::Parser, a: &mut A, {1}: usize) -> Result<A::{2}, {0}::parser::ParserError> {{",
+                            self.Crate, min, name));
+        } else {
+            e.Line("// Takes binary operators of at least the minimum level, levels counting from 1.");
+            e.Open(&format!("fn Climb(p: &mut {0}::parser::Parser, {1}: usize) -> Result<{2}, {0}::parser::ParserError> {{",
+                            self.Crate, min, name));
+            if let Some(Symbol::Rule(_)) = self.Sets.Symbols.Lookup(&precedence.Operand.Token.Literal) {
+                // Expect of the operand, in scope for def_parser! blocks only.
+                e.Line(&format!("use {}::parser::AstNodeParserTrait;", self.Crate));
+            }
+        }
+        e.Line("let begin = p.Token.Pos.Begin;");
+        let left = if binary { "let mut left" } else { "let left" };
+        if prefix {
+            e.Open(&format!("{} = match p.Token.Kind {{", left));
+            for (index, level) in levels.iter().enumerate().filter(|(_, level)| level.IsPrefix()) {
+                e.Open(&format!("{} => {{", kinds[index]));
+                e.Line("let op = p.GetTokenAndScan()?;");
+                e.Line(&format!("let operand = {};", climb(&(index + 1).to_string())));
+                match self.Acting {
+                    true => e.Line(&format!("a.{}Unary(op, operand, {})?", name, range)),
+                    false => e.Line(&format!("{0}::Unary(Box::new({0}Unary {{ Op: op, Operand: operand, Pos: {1} }}))", name, range)),
+                }
+                e.Close("}");
+            }
+            if self.Acting {
+                e.Open("_ => {");
+                e.Line(&format!("let operand = {};", expr));
+                e.Line(&format!("a.{}Operand(operand, {})?", name, range));
+                e.Close("}");
+            } else {
+                e.Line(&format!("_ => {}::Operand(Box::new({})),", name, expr));
+            }
+            e.Close("};");
+        } else if self.Acting {
+            e.Line(&format!("let operand = {};", expr));
+            e.Line(&format!("{} = a.{}Operand(operand, {})?;", left, name, range));
+        } else {
+            e.Line(&format!("{} = {}::Operand(Box::new({}));", left, name, expr));
+        }
+        if binary {
+            e.Open("loop {");
+            e.Open("let (level, right) = match p.Token.Kind {");
+            for (index, level) in levels.iter().enumerate().filter(|(_, level)| !level.IsPrefix()) {
+                e.Line(&format!("{} => ({}, {}),", kinds[index], index + 1, level.IsRight()));
+            }
+            e.Line("_ => break,");
+            e.Close("};");
+            e.Open("if level < min {");
+            e.Line("break;");
+            e.Close("}");
+            e.Line("let op = p.GetTokenAndScan()?;");
+            e.Line(&format!("let right = {};", climb("if right { level } else { level + 1 }")));
+            match self.Acting {
+                true => e.Line(&format!("left = a.{}Binary(left, op, right, {})?;", name, range)),
+                false => e.Line(&format!("left = {0}::Binary(Box::new({0}Binary {{ Left: left, Op: op, Right: right, Pos: {1} }}));", name, range)),
+            }
+            e.Close("}");
+        }
+        e.Line("Ok(left)");
+        e.Close("}");
+        e.Close("}");
+    }
+
+    fn Act(&mut self, def: &'a Def) -> Result<(), CodegenError> {
+        let name = &def.Name.Token.Literal;
+        match &def.Rule.Elements[..] {
+            [Node::Match(branch)] => self.ActEnum(name, branch),
+            [Node::Precedence(precedence)] => self.ActPrecedence(name, precedence),
+            nodes => {
+                let mut w = Writer::new(2);
+                self.Action(&mut w, name, name, nodes)?;
+                self.Actions.push((name.to_string(), w.Out));
+                Ok(())
+            }
+        }
+    }
+
+    // Parses the nodes and passes their fields to the method of the actions, which returns the value of def.
+    fn Action(&mut self, w: &mut Writer, def: &str, method: &str, nodes: &[Node]) -> Result<(), CodegenError> {
+        let mut fields: Vec<FieldInfo> = vec![];
+        self.CollectFields(def, nodes, false, &mut fields)?;
+
+        w.Line("let begin = p.Token.Pos.Begin;");
+        for field in fields.iter().filter(|field| field.Nested) {
+            w.Line(&format!("let mut {} = {}::ast::Optional::None;", Local(&field.Name), self.Crate));
+        }
+        self.Sequence(w, def, nodes, false)?;
+        let mut args: Vec<String> = fields.iter().map(|field| Local(&field.Name)).collect();
+        args.push("p.GetRange(begin)".to_string());
+        w.Line(&format!("a.{}({})?", method, args.join(", ")));
+
+        let mut params: Vec<String> = fields.iter().map(|field| match field.Nested {
+            true => format!("{}: {}::ast::Optional<{}>", Local(&field.Name), self.Crate, field.Type),
+            false => format!("{}: {}", Local(&field.Name), field.Type),
+        }).collect();
+        params.push(format!("pos: {}::scanner::PosRange", self.Crate));
+        self.Method(method, &params, def);
+        Ok(())
+    }
+
+    fn Method(&mut self, method: &str, params: &[String], def: &str) {
+        self.Methods.push(format!("fn {}(&mut self, {}) -> Result<Self::{}, {}::parser::ParserError>;", method, params.join(", "), def, self.Crate));
+    }
+
+    // A method per pattern, named after the definition and the variant of the pattern.
+    fn ActEnum(&mut self, name: &str, branch: &Branch) -> Result<(), CodegenError> {
+        let mut variants: Vec<String> = vec![];
+        let mut covered = TokenSet::new();
+
+        let mut w = Writer::new(2);
+        w.Open(&self.BranchMatch(branch));
+        let k = self.BranchLookahead(branch);
+        for pattern in &branch.Patterns.Elements {
+            let Some(arm) = self.AheadPattern(pattern, k, &mut covered) else { continue; };
+            let nodes = &pattern.Rule.Elements;
+
+            let mut fields: Vec<FieldInfo> = vec![];
+            self.CollectFields(name, nodes, false, &mut fields)?;
+            let taken: Vec<&str> = variants.iter().map(String::as_str).collect();
+            let variant = self.Variant(pattern, &fields, &taken);
+
+            w.Open(&format!("{} => {{", arm));
+            self.Action(&mut w, name, &format!("{}{}", name, variant), nodes)?;
+            w.Close("}");
+            variants.push(variant);
+        }
+        if !covered.contains(WILDCARD) {
+            w.Line(&format!("_ => {0}::unexpected_token!({0}::ast::TokenKind::None, p.Token.clone()),", self.Crate));
+        }
+        w.Close("}");
+
+        self.Actions.push((name.to_string(), w.Out));
+        Ok(())
+    }
+
+    fn ActPrecedence(&mut self, name: &str, precedence: &PrecedenceRule) -> Result<(), CodegenError> {
+        let levels = &precedence.Levels.Elements;
+        let (operand, _) = self.Value(&precedence.Operand);
+        let (op, pos) = (format!("op: {}", self.TokenType()), format!("pos: {}::scanner::PosRange", self.Crate));
+
+        self.Method(&format!("{}Operand", name), &[format!("operand: {}", operand), pos.clone()], name);
+        if levels.iter().any(|level| !level.IsPrefix()) {
+            let (left, right) = (format!("left: Self::{}", name), format!("right: Self::{}", name));
+            self.Method(&format!("{}Binary", name), &[left, op.clone(), right, pos.clone()], name);
+        }
+        if levels.iter().any(|level| level.IsPrefix()) {
+            self.Method(&format!("{}Unary", name), &[op, format!("operand: Self::{}", name), pos], name);
+        }
+
+        let mut e = Writer::new(0);
+        self.Climb(&mut e, name, precedence);
+        self.Enums.push(e.Out);
+
+        let mut w = Writer::new(2);
+        w.Line(&format!("{}::ClimbWith(p, a, 1)?", name));
+        self.Actions.push((name.to_string(), w.Out));
+        Ok(())
+    }
+
+    fn Finish(self) -> String {
+        let mut w = Writer::new(0);
+        w.Line("// Code generated by proc-parser. DO NOT EDIT.");
+
+        // Grammars declaring or quoting their tokens are scanned with their own table.
+        if !self.Sets.Symbols.Literals.is_empty() {
+            let mut lookup: Vec<(String, TokenKind)> = self.Sets.Symbols.KeywordLookup().into_iter().collect();
+            lookup.sort_by(|a, b| a.0.cmp(&b.0));
+
+            w.Line("");
+            w.Line("// Parser.KeywordLookup for this grammar.");
+            w.Open(&format!("pub fn KeywordLookup() -> std::collections::HashMap<String, {}::ast::TokenKind> {{", self.Crate));
+            w.Open("std::collections::HashMap::from([");
+            for (literal, kind) in &lookup {
+                w.Line(&format!("({:?}.to_string(), {}),", literal, self.KindExpr(kind)));
+            }
+            w.Close("])");
+            w.Close("}");
+        }
+
+        // Single quotes are strings to grammars not naming Char.
+        if self.Sets.Symbols.CharLiterals {
+            w.Line("");
+            w.Line("// Parser.Scanner.CharLiterals for this grammar.");
+            w.Line("pub const CHAR_LITERALS: bool = true;");
+        }
+
+        if !self.Structs.is_empty() {
+            w.Line("");
+            w.Open(&format!("{}::def_ast! {{", self.Crate));
+            for (index, (name, members)) in self.Structs.iter().enumerate() {
+                if index > 0 {
+                    w.Line("");
+                }
+                self.Doc(&mut w, name);
+                w.Open(&format!("{} {{", name));
+                for (member, typ) in members {
+                    w.Line(&format!("{}: {},", member, typ));
+                }
+                w.Close(if index + 1 < self.Structs.len() { "}," } else { "}" });
+            }
+            w.Close("}");
+        }
+
+        for e in &self.Enums {
+            w.Line("");
+            w.Out.push_str(e);
+        }
+
+        if !self.Parsers.is_empty() {
+            w.Line("");
+            w.Open(&format!("{}::def_parser! {{", self.Crate));
+            self.Entries(&mut w, &self.Parsers, |name| format!("{}, p => {{", name));
+            w.Close("}");
+        }
+
+        if !self.Actions.is_empty() {
+            w.Line("");
+            w.Line("// Values built while parsing, e.g. by `<File as AstNodeActionTrait<_>>::Act(&mut p, &mut actions)`.");
+            w.Open("pub trait Actions {");
+            for (name, _) in &self.Actions {
+                w.Line(&format!("type {};", name));
+            }
+            w.Line("");
+            for method in &self.Methods {
+                w.Line(method);
+            }
+            w.Close("}");
+            w.Line("");
+            w.Open(&format!("{}::def_action! {{", self.Crate));
+            w.Line("A: Actions;");
+            w.Line("");
+            self.Entries(&mut w, &self.Actions, |name| format!("{0} => {0}, p, a => {{", name));
+            w.Close("}");
+        }
+
+        w.Out
+    }
+
+    // Entries of def_parser! or def_action!, with the attributes and the expectation annotated to their definitions.
+    fn Entries(&self, w: &mut Writer, entries: &[(String, String)], header: fn(&str) -> String) {
+        for (index, (name, body)) in entries.iter().enumerate() {
+            if index > 0 {
+                w.Line("");
+            }
+            if self.Annotation(name, "inline").is_some() {
+                w.Line("#[inline]");
+            }
+            w.Open(&header(name));
+            match self.Annotation(name, "name").and_then(Annotation::Text) {
+                Some(text) => {
+                    w.Open(&format!("p.Expecting({:?}, |p| Ok({{", text));
+                    for line in body.lines() {
+                        w.Line(line.strip_prefix(&"    ".repeat(w.Indent - 1)).unwrap_or(line));
+                    }
+                    w.Close("}))?");
+                }
+                None => w.Out.push_str(body),
+            }
+            w.Close(if index + 1 < entries.len() { "}," } else { "}" });
+        }
+    }
+}
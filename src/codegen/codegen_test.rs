@@ -0,0 +1,285 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use crate::ast::{File, List, Optional, Token};
+use crate::codegen::*;
+use crate::interpreter::{Grammar, Tree, TreeNode};
+use crate::ast::TokenKind;
+use crate::parser::{AstNodeActionTrait, AstNodeParserTrait, Parser, ParserError};
+use crate::scanner::PosRange;
+
+mod value {
+    include!("testdata/value.rs");
+}
+
+mod expr {
+    include!("testdata/expr.rs");
+}
+
+mod calc {
+    include!("testdata/calc.rs");
+}
+
+fn parse(src: &str) -> File {
+    let mut p = File::NewParser(src);
+    p.Scan().unwrap();
+    File::Expect(&mut p).unwrap()
+}
+
+#[test]
+fn TestGenerator_Generate() {
+    let file = parse(include_str!("testdata/value.ppg"));
+
+    let code = Generator { CratePath: "crate".to_string(), Actions: false }.Generate(&file).unwrap();
+
+    // Regenerate testdata/value.rs from this output after changing the generator.
+    println!("{}", code);
+    assert_eq!(code, include_str!("testdata/value.rs"));
+}
+
+#[test]
+fn TestGenerator_Parse() {
+    let mut p = Parser::new("a: {k: (x, 1, [y, z])}.t\nb: .;\nc: d: e.f\nc: d: e.f.g\nf: g(h, i(),)\ng: :x y z\n".chars().collect());
+    p.Scan().unwrap();
+
+    let program = value::Program::Expect(&mut p).unwrap();
+
+    let stmts = &program.Stmts.Elements;
+    assert_eq!(stmts.len(), 6);
+    assert_eq!(stmts[0].Name.Literal, "a");
+    assert!(matches!(&stmts[0].Tag, Optional::Some(tag) if tag.Literal == "t"));
+    match &stmts[0].Value {
+        value::Value::Lbrace(entry) => {
+            assert_eq!(entry.Key.Literal, "k");
+            match &entry.Value {
+                value::Value::Items(items) => assert_eq!(items.Elements.len(), 3),
+                _ => panic!()
+            }
+        }
+        _ => panic!()
+    }
+    assert!(matches!(stmts[1].Value, value::Value::Dot));
+    assert!(matches!(&stmts[2].Ref, Optional::Some(r) if r.Target.Literal == "f" && matches!(r.Member, Optional::None)));
+    assert!(matches!(&stmts[3].Ref, Optional::Some(r) if matches!(&r.Member, Optional::Some(m) if m.Literal == "g")));
+    match &stmts[4].Value {
+        value::Value::Call(call) => {
+            assert_eq!(call.Name.Literal, "g");
+            assert!(matches!(&call.Args.Elements[..], [value::Value::Ref(h), value::Value::Call(_)] if h.Literal == "h"));
+        }
+        _ => panic!()
+    }
+    assert!(matches!(&stmts[5].Value, value::Value::Words(words) if words.Elements.len() == 3));
+}
+
+#[test]
+fn TestGenerator_Unsupported() {
+    let file = parse("File := ($items:Ident, File, EOF)\n");
+
+    assert!(matches!(Generator::new().Generate(&file), Err(CodegenError::Unsupported(_))));
+}
+
+#[test]
+fn TestGenerator_Tokens() {
+    let file = parse("token ASSIGN \":=\"\nkeyword LET \"let\"\n\nFile := LET, $name:Ident, ASSIGN, $value:Int, EOF\n");
+
+    let code = Generator::new().Generate(&file).unwrap();
+
+    assert!(code.contains("pub fn KeywordLookup()"));
+    assert!(code.contains("(\"let\".to_string(), proc_parser::ast::TokenKind::Declared(\"LET\")),"));
+    assert!(code.contains("p.MatchAndScan(proc_parser::ast::TokenKind::Declared(\"ASSIGN\"))?;"));
+    assert!(!code.contains("CHAR_LITERALS"));
+
+    let code = Generator::new().Generate(&parse("File := $value:Char, EOF\n")).unwrap();
+    assert!(code.contains("pub const CHAR_LITERALS: bool = true;"));
+}
+
+#[test]
+fn TestGenerator_Default() {
+    let file = parse("File := $name:Ident, $value:Value, EOF\nValue := {\n    COLON => COLON, $int:Int\n    _ => ;\n}\n");
+
+    let code = Generator::new().Generate(&file).unwrap();
+
+    assert!(code.contains("    Int(Box<proc_parser::ast::Token>),\n    Other,\n"));
+    assert!(code.contains("            _ => {\n                Value::Other\n            }\n"));
+    assert!(!code.contains("unexpected_token!"));
+}
+
+#[test]
+fn TestGenerator_Templates() {
+    let file = parse("File := $pair:Pair<Ident, Int>, EOF\nPair<A, B> := $a:A, $b:B\n");
+
+    let code = Generator::new().Generate(&file).unwrap();
+
+    assert!(code.contains("    PairIdentInt {\n"));
+    assert!(code.contains("        Pair: Box<PairIdentInt>,\n"));
+    assert!(!code.contains("Pair<"));
+}
+
+#[test]
+fn TestGenerator_Precedence() {
+    let file = parse(include_str!("testdata/expr.ppg"));
+    let code = Generator { CratePath: "crate".to_string(), Actions: false }.Generate(&file).unwrap();
+    assert_eq!(code, include_str!("testdata/expr.rs"));
+
+    let mut p = Parser::new("1 - 2 - -a * b ^ c ^ d\n".chars().collect());
+    p.KeywordLookup = expr::KeywordLookup();
+    p.Scan().unwrap();
+
+    let file = expr::File::Expect(&mut p).unwrap();
+
+    // (1 - 2) - ((-a) * (b ^ (c ^ d)))
+    let sub = match &file.Exprs.Elements[..] {
+        [expr::Expr::Binary(sub)] => sub,
+        _ => panic!()
+    };
+    assert!(matches!(&sub.Left, expr::Expr::Binary(left) if left.Op.Literal == "-"));
+    assert_eq!((sub.Pos.Begin.Offset, sub.Pos.End.Offset), (0, 22));
+    match &sub.Right {
+        expr::Expr::Binary(mul) => {
+            assert_eq!(mul.Op.Literal, "*");
+            assert!(matches!(&mul.Left, expr::Expr::Unary(neg) if matches!(&neg.Operand, expr::Expr::Operand(_))));
+            assert!(matches!(&mul.Right, expr::Expr::Binary(pow) if matches!(&pow.Right, expr::Expr::Binary(_))));
+        }
+        _ => panic!()
+    }
+
+    // Errors where an annotated rule starts name the rule.
+    for (input, want) in [("1 + )\n", "operand"), (")\n", "expression")] {
+        let mut p = Parser::new(input.chars().collect());
+        p.KeywordLookup = expr::KeywordLookup();
+        p.Scan().unwrap();
+        match expr::File::Expect(&mut p) {
+            Err(ParserError::Expected(e)) => assert_eq!(e.Name, want),
+            _ => panic!()
+        }
+        // Recovering is left to the caller, see Annotation.
+        p.SkipTo(expr::Atom::RECOVER).unwrap();
+        assert!(matches!(p.Token.Kind, TokenKind::RPAREN));
+    }
+}
+
+#[test]
+fn TestGenerator_Ranges() {
+    fn node(tree: &Tree) -> &TreeNode {
+        match tree {
+            Tree::Node(node) => node,
+            _ => panic!("not a node")
+        }
+    }
+
+    // Generated parsers range nodes like the interpreter.
+    let input = "a: f(1, (2))\nb: x: y.z.w\n";
+    let tree = Grammar::Load(include_str!("testdata/value.ppg")).unwrap().Parse(input).unwrap();
+    let mut p = Parser::new(input.chars().collect());
+    p.Scan().unwrap();
+    let program = value::Program::Expect(&mut p).unwrap();
+
+    assert_eq!(program.Pos.to_string(), tree.Pos().to_string());
+    let stmts = match node(&tree).Get("stmts") {
+        Some(Tree::List(stmts)) => stmts,
+        _ => panic!()
+    };
+    assert_eq!(program.Stmts.Pos.to_string(), stmts.Pos.to_string());
+    for (stmt, tree) in program.Stmts.Elements.iter().zip(&stmts.Elements) {
+        assert_eq!(stmt.Pos.to_string(), tree.Pos().to_string());
+    }
+    match (&program.Stmts.Elements[0].Value, node(&stmts.Elements[0]).Get("value")) {
+        (value::Value::Call(call), Some(value)) => {
+            let tree = node(value).Get("call").unwrap();
+            assert_eq!(call.Pos.to_string(), tree.Pos().to_string());
+            assert_eq!(call.Args.Pos.to_string(), node(tree).Get("args").unwrap().Pos().to_string());
+        }
+        _ => panic!()
+    }
+    match (&program.Stmts.Elements[1].Ref, node(&stmts.Elements[1]).Get("ref")) {
+        (Optional::Some(ref_), Some(tree)) => assert_eq!(ref_.Pos.to_string(), tree.Pos().to_string()),
+        _ => panic!()
+    }
+
+    let input = "-a * (b - 1)\n";
+    let tree = Grammar::Load(include_str!("testdata/expr.ppg")).unwrap().Parse(input).unwrap();
+    let mut p = Parser::new(input.chars().collect());
+    p.KeywordLookup = expr::KeywordLookup();
+    p.Scan().unwrap();
+    let file = expr::File::Expect(&mut p).unwrap();
+
+    assert_eq!(file.Pos.to_string(), tree.Pos().to_string());
+    let exprs = match node(&tree).Get("exprs") {
+        Some(Tree::List(exprs)) => exprs,
+        _ => panic!()
+    };
+    match (&file.Exprs.Elements[..], &exprs.Elements[..]) {
+        ([expr::Expr::Binary(mul)], [tree]) => {
+            assert_eq!(mul.Pos.to_string(), tree.Pos().to_string());
+            assert_eq!((mul.Pos.Begin.Offset, mul.Pos.End.Offset), (0, 12));
+            match &mul.Left {
+                expr::Expr::Unary(neg) => assert_eq!(neg.Pos.to_string(), node(tree).Get("left").unwrap().Pos().to_string()),
+                _ => panic!()
+            }
+        }
+        _ => panic!()
+    }
+}
+
+#[test]
+fn TestGenerator_Actions() {
+    let file = parse(include_str!("testdata/calc.ppg"));
+    let code = Generator { CratePath: "crate".to_string(), Actions: true }.Generate(&file).unwrap();
+    assert_eq!(code, include_str!("testdata/calc.rs"));
+
+    // Evaluates without building the tree, counting the calls.
+    struct Calc {
+        Calls: usize,
+    }
+
+    impl calc::Actions for Calc {
+        type File = Vec<f64>;
+        type Expr = f64;
+        type Atom = f64;
+        type Call = f64;
+
+        fn File(&mut self, exprs: List<f64>, _: PosRange) -> Result<Vec<f64>, ParserError> { Ok(exprs.Elements) }
+        fn ExprOperand(&mut self, operand: f64, _: PosRange) -> Result<f64, ParserError> { Ok(operand) }
+        fn ExprBinary(&mut self, left: f64, op: Token, right: f64, _: PosRange) -> Result<f64, ParserError> {
+            Ok(match op.Literal.as_str() {
+                "+" => left + right,
+                "-" => left - right,
+                _ => left * right,
+            })
+        }
+        fn ExprUnary(&mut self, _: Token, operand: f64, _: PosRange) -> Result<f64, ParserError> { Ok(-operand) }
+        fn AtomInt(&mut self, int: Token, _: PosRange) -> Result<f64, ParserError> { Ok(int.Literal.parse().unwrap()) }
+        fn AtomInner(&mut self, inner: f64, _: PosRange) -> Result<f64, ParserError> { Ok(inner) }
+        fn AtomVar(&mut self, var: Token, _: PosRange) -> Result<f64, ParserError> {
+            assert_eq!(var.Literal, "x");
+            Ok(1.0)
+        }
+        fn AtomCall(&mut self, call: f64, _: PosRange) -> Result<f64, ParserError> { Ok(call) }
+        fn Call(&mut self, name: Token, args: List<f64>, _: PosRange) -> Result<f64, ParserError> {
+            assert_eq!(name.Literal, "max");
+            self.Calls += 1;
+            Ok(args.Elements.into_iter().fold(f64::MIN, f64::max))
+        }
+    }
+
+    let mut p = Parser::new("1 + 2 * (3 - x)\n-max(1, 4 * 2) - 1\n".chars().collect());
+    p.KeywordLookup = calc::KeywordLookup();
+    p.Scan().unwrap();
+
+    let mut actions = Calc { Calls: 0 };
+    let values = <calc::File as AstNodeActionTrait<_>>::Act(&mut p, &mut actions).unwrap();
+    assert_eq!(values, [5.0, -9.0]);
+    assert_eq!(actions.Calls, 1);
+
+    // Errors are the same as parsing the tree, `!")"` rejects empty parentheses.
+    for input in ["1 + )\n", "1 + ()\n"] {
+        let mut p = Parser::new(input.chars().collect());
+        p.KeywordLookup = calc::KeywordLookup();
+        p.Scan().unwrap();
+        match <calc::File as AstNodeActionTrait<_>>::Act(&mut p, &mut actions) {
+            Err(ParserError::UnexpectedToken(e)) => assert_eq!(e.Have.Literal, ")"),
+            _ => panic!()
+        }
+    }
+}
@@ -0,0 +1,8 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+pub use codegen::*;
+
+pub mod codegen;
+mod codegen_test;
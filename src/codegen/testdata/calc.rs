@@ -0,0 +1,269 @@
+// Code generated by proc-parser. DO NOT EDIT.
+
+// Parser.KeywordLookup for this grammar.
+pub fn KeywordLookup() -> std::collections::HashMap<String, crate::ast::TokenKind> {
+    std::collections::HashMap::from([
+        ("\n".to_string(), crate::ast::TokenKind::NEWLINE),
+        ("!".to_string(), crate::ast::TokenKind::NOT),
+        ("$".to_string(), crate::ast::TokenKind::FIELD),
+        ("&".to_string(), crate::ast::TokenKind::AND),
+        ("(".to_string(), crate::ast::TokenKind::LPAREN),
+        (")".to_string(), crate::ast::TokenKind::RPAREN),
+        ("*".to_string(), crate::ast::TokenKind::STAR),
+        ("+".to_string(), crate::ast::TokenKind::PLUS),
+        (",".to_string(), crate::ast::TokenKind::COMMA),
+        ("-".to_string(), crate::ast::TokenKind::Declared("\"-\"")),
+        (".".to_string(), crate::ast::TokenKind::DOT),
+        (":".to_string(), crate::ast::TokenKind::COLON),
+        (":=".to_string(), crate::ast::TokenKind::DEFINE),
+        (";".to_string(), crate::ast::TokenKind::SEMICOLON),
+        ("<".to_string(), crate::ast::TokenKind::LSS),
+        ("=>".to_string(), crate::ast::TokenKind::ARROW),
+        (">".to_string(), crate::ast::TokenKind::GTR),
+        ("@".to_string(), crate::ast::TokenKind::AT),
+        ("[".to_string(), crate::ast::TokenKind::LBRACK),
+        ("]".to_string(), crate::ast::TokenKind::RBRACK),
+        ("{".to_string(), crate::ast::TokenKind::LBRACE),
+        ("}".to_string(), crate::ast::TokenKind::RBRACE),
+    ])
+}
+
+crate::def_ast! {
+    File {
+        Exprs: crate::ast::List<Expr>,
+    },
+
+    ExprBinary {
+        Left: Expr,
+        Op: crate::ast::Token,
+        Right: Expr,
+    },
+
+    ExprUnary {
+        Op: crate::ast::Token,
+        Operand: Expr,
+    },
+
+    Call {
+        Name: crate::ast::Token,
+        Args: crate::ast::List<Expr>,
+    }
+}
+
+pub enum Expr {
+    None,
+    Operand(Box<Atom>),
+    Binary(Box<ExprBinary>),
+    Unary(Box<ExprUnary>),
+}
+
+impl Default for Expr {
+    fn default() -> Self { Expr::None }
+}
+
+impl Expr {
+    // Takes binary operators of at least the minimum level, levels counting from 1.
+    fn Climb(p: &mut crate::parser::Parser, min: usize) -> Result<Expr, crate::parser::ParserError> {
+        use crate::parser::AstNodeParserTrait;
+        let begin = p.Token.Pos.Begin;
+        let mut left = match p.Token.Kind {
+            crate::ast::TokenKind::Declared("\"-\"") => {
+                let op = p.GetTokenAndScan()?;
+                let operand = Expr::Climb(p, 3)?;
+                Expr::Unary(Box::new(ExprUnary { Op: op, Operand: operand, Pos: p.GetRange(begin) }))
+            }
+            _ => Expr::Operand(Box::new(Atom::Expect(p)?)),
+        };
+        loop {
+            let (level, right) = match p.Token.Kind {
+                crate::ast::TokenKind::PLUS | crate::ast::TokenKind::Declared("\"-\"") => (1, false),
+                crate::ast::TokenKind::STAR => (2, false),
+                _ => break,
+            };
+            if level < min {
+                break;
+            }
+            let op = p.GetTokenAndScan()?;
+            let right = Expr::Climb(p, if right { level } else { level + 1 })?;
+            left = Expr::Binary(Box::new(ExprBinary { Left: left, Op: op, Right: right, Pos: p.GetRange(begin) }));
+        }
+        Ok(left)
+    }
+}
+
+pub enum Atom {
+    None,
+    Int(Box<crate::ast::Token>),
+    Inner(Box<Expr>),
+    Var(Box<crate::ast::Token>),
+    Call(Box<Call>),
+}
+
+impl Default for Atom {
+    fn default() -> Self { Atom::None }
+}
+
+impl Expr {
+    // Climb building the values of the actions.
+    fn ClimbWith<A: Actions>(p: &mut crate::parser::Parser, a: &mut A, min: usize) -> Result<A::Expr, crate::parser::ParserError> {
+        let begin = p.Token.Pos.Begin;
+        let mut left = match p.Token.Kind {
+            crate::ast::TokenKind::Declared("\"-\"") => {
+                let op = p.GetTokenAndScan()?;
+                let operand = Expr::ClimbWith(p, a, 3)?;
+                a.ExprUnary(op, operand, p.GetRange(begin))?
+            }
+            _ => {
+                let operand = <Atom as crate::parser::AstNodeActionTrait<A>>::Act(p, a)?;
+                a.ExprOperand(operand, p.GetRange(begin))?
+            }
+        };
+        loop {
+            let (level, right) = match p.Token.Kind {
+                crate::ast::TokenKind::PLUS | crate::ast::TokenKind::Declared("\"-\"") => (1, false),
+                crate::ast::TokenKind::STAR => (2, false),
+                _ => break,
+            };
+            if level < min {
+                break;
+            }
+            let op = p.GetTokenAndScan()?;
+            let right = Expr::ClimbWith(p, a, if right { level } else { level + 1 })?;
+            left = a.ExprBinary(left, op, right, p.GetRange(begin))?;
+        }
+        Ok(left)
+    }
+}
+
+crate::def_parser! {
+    File, p => {
+        let begin = p.Token.Pos.Begin;
+        let exprs = crate::ast::List::<Expr>::Expect(p, crate::ast::TokenKind::SEMICOLON, crate::ast::TokenKind::EOF)?;
+
+        File {
+            Exprs: exprs,
+            Pos: p.GetRange(begin),
+        }
+    },
+
+    Expr, p => {
+        p.Expecting("expression", |p| Ok({
+            Expr::Climb(p, 1)?
+        }))?
+    },
+
+    Atom, p => {
+        match (p.Token.Kind.clone(), p.Peek(1)?.Kind.clone()) {
+            (crate::ast::TokenKind::Int(_), _) => {
+                let int = { p.Match(crate::ast::TokenKind::Int(crate::scanner::IntFormat::DEC))?; p.GetTokenAndScan()? };
+                Atom::Int(Box::new(int))
+            }
+            (crate::ast::TokenKind::LPAREN, _) => {
+                p.MatchAndScan(crate::ast::TokenKind::LPAREN)?;
+                if matches!(p.Token.Kind, crate::ast::TokenKind::RPAREN) {
+                    crate::unexpected_token!(crate::ast::TokenKind::None, p.Token.clone());
+                }
+                let inner = Expr::Expect(p)?;
+                p.MatchAndScan(crate::ast::TokenKind::RPAREN)?;
+                Atom::Inner(Box::new(inner))
+            }
+            (crate::ast::TokenKind::Ident, _) if !matches!(p.Peek(1)?.Kind, crate::ast::TokenKind::LPAREN) => {
+                let var = { p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan()? };
+                Atom::Var(Box::new(var))
+            }
+            (crate::ast::TokenKind::Ident, _) => {
+                let call = Box::new(Call::Expect(p)?);
+                Atom::Call(call)
+            }
+            _ => crate::unexpected_token!(crate::ast::TokenKind::None, p.Token.clone()),
+        }
+    },
+
+    Call, p => {
+        let begin = p.Token.Pos.Begin;
+        let name = { p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan()? };
+        p.MatchAndScan(crate::ast::TokenKind::LPAREN)?;
+        let args = crate::ast::List::<Expr>::Repeat(p, crate::ast::TokenKind::COMMA, true, |token| matches!(token.Kind, crate::ast::TokenKind::Declared("\"-\"") | crate::ast::TokenKind::Ident | crate::ast::TokenKind::Int(_) | crate::ast::TokenKind::LPAREN), Expr::Expect)?;
+        p.MatchAndScan(crate::ast::TokenKind::RPAREN)?;
+
+        Call {
+            Name: name,
+            Args: args,
+            Pos: p.GetRange(begin),
+        }
+    }
+}
+
+// Values built while parsing, e.g. by `<File as AstNodeActionTrait<_>>::Act(&mut p, &mut actions)`.
+pub trait Actions {
+    type File;
+    type Expr;
+    type Atom;
+    type Call;
+
+    fn File(&mut self, exprs: crate::ast::List<Self::Expr>, pos: crate::scanner::PosRange) -> Result<Self::File, crate::parser::ParserError>;
+    fn ExprOperand(&mut self, operand: Self::Atom, pos: crate::scanner::PosRange) -> Result<Self::Expr, crate::parser::ParserError>;
+    fn ExprBinary(&mut self, left: Self::Expr, op: crate::ast::Token, right: Self::Expr, pos: crate::scanner::PosRange) -> Result<Self::Expr, crate::parser::ParserError>;
+    fn ExprUnary(&mut self, op: crate::ast::Token, operand: Self::Expr, pos: crate::scanner::PosRange) -> Result<Self::Expr, crate::parser::ParserError>;
+    fn AtomInt(&mut self, int: crate::ast::Token, pos: crate::scanner::PosRange) -> Result<Self::Atom, crate::parser::ParserError>;
+    fn AtomInner(&mut self, inner: Self::Expr, pos: crate::scanner::PosRange) -> Result<Self::Atom, crate::parser::ParserError>;
+    fn AtomVar(&mut self, var: crate::ast::Token, pos: crate::scanner::PosRange) -> Result<Self::Atom, crate::parser::ParserError>;
+    fn AtomCall(&mut self, call: Self::Call, pos: crate::scanner::PosRange) -> Result<Self::Atom, crate::parser::ParserError>;
+    fn Call(&mut self, name: crate::ast::Token, args: crate::ast::List<Self::Expr>, pos: crate::scanner::PosRange) -> Result<Self::Call, crate::parser::ParserError>;
+}
+
+crate::def_action! {
+    A: Actions;
+
+    File => File, p, a => {
+        let begin = p.Token.Pos.Begin;
+        let exprs = crate::ast::List::ExpectWith(p, crate::ast::TokenKind::SEMICOLON, crate::ast::TokenKind::EOF, |p| <Expr as crate::parser::AstNodeActionTrait<A>>::Act(p, a))?;
+        a.File(exprs, p.GetRange(begin))?
+    },
+
+    Expr => Expr, p, a => {
+        p.Expecting("expression", |p| Ok({
+            Expr::ClimbWith(p, a, 1)?
+        }))?
+    },
+
+    Atom => Atom, p, a => {
+        match (p.Token.Kind.clone(), p.Peek(1)?.Kind.clone()) {
+            (crate::ast::TokenKind::Int(_), _) => {
+                let begin = p.Token.Pos.Begin;
+                let int = { p.Match(crate::ast::TokenKind::Int(crate::scanner::IntFormat::DEC))?; p.GetTokenAndScan()? };
+                a.AtomInt(int, p.GetRange(begin))?
+            }
+            (crate::ast::TokenKind::LPAREN, _) => {
+                let begin = p.Token.Pos.Begin;
+                p.MatchAndScan(crate::ast::TokenKind::LPAREN)?;
+                if matches!(p.Token.Kind, crate::ast::TokenKind::RPAREN) {
+                    crate::unexpected_token!(crate::ast::TokenKind::None, p.Token.clone());
+                }
+                let inner = <Expr as crate::parser::AstNodeActionTrait<A>>::Act(p, a)?;
+                p.MatchAndScan(crate::ast::TokenKind::RPAREN)?;
+                a.AtomInner(inner, p.GetRange(begin))?
+            }
+            (crate::ast::TokenKind::Ident, _) if !matches!(p.Peek(1)?.Kind, crate::ast::TokenKind::LPAREN) => {
+                let begin = p.Token.Pos.Begin;
+                let var = { p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan()? };
+                a.AtomVar(var, p.GetRange(begin))?
+            }
+            (crate::ast::TokenKind::Ident, _) => {
+                let begin = p.Token.Pos.Begin;
+                let call = <Call as crate::parser::AstNodeActionTrait<A>>::Act(p, a)?;
+                a.AtomCall(call, p.GetRange(begin))?
+            }
+            _ => crate::unexpected_token!(crate::ast::TokenKind::None, p.Token.clone()),
+        }
+    },
+
+    Call => Call, p, a => {
+        let begin = p.Token.Pos.Begin;
+        let name = { p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan()? };
+        p.MatchAndScan(crate::ast::TokenKind::LPAREN)?;
+        let args = crate::ast::List::Repeat(p, crate::ast::TokenKind::COMMA, true, |token| matches!(token.Kind, crate::ast::TokenKind::Declared("\"-\"") | crate::ast::TokenKind::Ident | crate::ast::TokenKind::Int(_) | crate::ast::TokenKind::LPAREN), |p| <Expr as crate::parser::AstNodeActionTrait<A>>::Act(p, a))?;
+        p.MatchAndScan(crate::ast::TokenKind::RPAREN)?;
+        a.Call(name, args, p.GetRange(begin))?
+    }
+}
@@ -0,0 +1,148 @@
+// Code generated by proc-parser. DO NOT EDIT.
+
+// Parser.KeywordLookup for this grammar.
+pub fn KeywordLookup() -> std::collections::HashMap<String, crate::ast::TokenKind> {
+    std::collections::HashMap::from([
+        ("\n".to_string(), crate::ast::TokenKind::NEWLINE),
+        ("!".to_string(), crate::ast::TokenKind::NOT),
+        ("$".to_string(), crate::ast::TokenKind::FIELD),
+        ("&".to_string(), crate::ast::TokenKind::AND),
+        ("(".to_string(), crate::ast::TokenKind::LPAREN),
+        (")".to_string(), crate::ast::TokenKind::RPAREN),
+        ("*".to_string(), crate::ast::TokenKind::STAR),
+        ("+".to_string(), crate::ast::TokenKind::PLUS),
+        (",".to_string(), crate::ast::TokenKind::COMMA),
+        ("-".to_string(), crate::ast::TokenKind::Declared("\"-\"")),
+        (".".to_string(), crate::ast::TokenKind::DOT),
+        (":".to_string(), crate::ast::TokenKind::COLON),
+        (":=".to_string(), crate::ast::TokenKind::DEFINE),
+        (";".to_string(), crate::ast::TokenKind::SEMICOLON),
+        ("<".to_string(), crate::ast::TokenKind::LSS),
+        ("=>".to_string(), crate::ast::TokenKind::ARROW),
+        (">".to_string(), crate::ast::TokenKind::GTR),
+        ("@".to_string(), crate::ast::TokenKind::AT),
+        ("[".to_string(), crate::ast::TokenKind::LBRACK),
+        ("]".to_string(), crate::ast::TokenKind::RBRACK),
+        ("^".to_string(), crate::ast::TokenKind::Declared("\"^\"")),
+        ("{".to_string(), crate::ast::TokenKind::LBRACE),
+        ("}".to_string(), crate::ast::TokenKind::RBRACE),
+    ])
+}
+
+crate::def_ast! {
+    /// One expression per line.
+    File {
+        Exprs: crate::ast::List<Expr>,
+    },
+
+    ExprBinary {
+        Left: Expr,
+        Op: crate::ast::Token,
+        Right: Expr,
+    },
+
+    ExprUnary {
+        Op: crate::ast::Token,
+        Operand: Expr,
+    }
+}
+
+/// Binary operators from the loosest to the tightest,
+/// `-` is also a prefix binding tighter than `*`.
+pub enum Expr {
+    None,
+    Operand(Box<Atom>),
+    Binary(Box<ExprBinary>),
+    Unary(Box<ExprUnary>),
+}
+
+impl Default for Expr {
+    fn default() -> Self { Expr::None }
+}
+
+impl Expr {
+    // Takes binary operators of at least the minimum level, levels counting from 1.
+    fn Climb(p: &mut crate::parser::Parser, min: usize) -> Result<Expr, crate::parser::ParserError> {
+        use crate::parser::AstNodeParserTrait;
+        let begin = p.Token.Pos.Begin;
+        let mut left = match p.Token.Kind {
+            crate::ast::TokenKind::Declared("\"-\"") => {
+                let op = p.GetTokenAndScan()?;
+                let operand = Expr::Climb(p, 3)?;
+                Expr::Unary(Box::new(ExprUnary { Op: op, Operand: operand, Pos: p.GetRange(begin) }))
+            }
+            _ => Expr::Operand(Box::new(Atom::Expect(p)?)),
+        };
+        loop {
+            let (level, right) = match p.Token.Kind {
+                crate::ast::TokenKind::PLUS | crate::ast::TokenKind::Declared("\"-\"") => (1, false),
+                crate::ast::TokenKind::STAR => (2, false),
+                crate::ast::TokenKind::Declared("\"^\"") => (4, true),
+                _ => break,
+            };
+            if level < min {
+                break;
+            }
+            let op = p.GetTokenAndScan()?;
+            let right = Expr::Climb(p, if right { level } else { level + 1 })?;
+            left = Expr::Binary(Box::new(ExprBinary { Left: left, Op: op, Right: right, Pos: p.GetRange(begin) }));
+        }
+        Ok(left)
+    }
+}
+
+pub enum Atom {
+    None,
+    Name(Box<crate::ast::Token>),
+    Int(Box<crate::ast::Token>),
+    Inner(Box<Expr>),
+}
+
+impl Default for Atom {
+    fn default() -> Self { Atom::None }
+}
+
+impl Atom {
+    pub const RECOVER: &'static [crate::ast::TokenKind] = &[crate::ast::TokenKind::SEMICOLON, crate::ast::TokenKind::RPAREN];
+}
+
+crate::def_parser! {
+    File, p => {
+        let begin = p.Token.Pos.Begin;
+        let exprs = crate::ast::List::<Expr>::Expect(p, crate::ast::TokenKind::SEMICOLON, crate::ast::TokenKind::EOF)?;
+
+        File {
+            Exprs: exprs,
+            Pos: p.GetRange(begin),
+        }
+    },
+
+    Expr, p => {
+        p.Expecting("expression", |p| Ok({
+            Expr::Climb(p, 1)?
+        }))?
+    },
+
+    #[inline]
+    Atom, p => {
+        p.Expecting("operand", |p| Ok({
+            match p.Token.Kind {
+                crate::ast::TokenKind::Ident => {
+                    let name = { p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan()? };
+                    Atom::Name(Box::new(name))
+                }
+                crate::ast::TokenKind::Int(_) => {
+                    let int = { p.Match(crate::ast::TokenKind::Int(crate::scanner::IntFormat::DEC))?; p.GetTokenAndScan()? };
+                    Atom::Int(Box::new(int))
+                }
+                crate::ast::TokenKind::LPAREN => {
+                    p.MatchAndScan(crate::ast::TokenKind::LPAREN)?;
+                    let inner = Expr::Expect(p)?;
+                    p.MatchAndScan(crate::ast::TokenKind::RPAREN)?;
+                    Atom::Inner(Box::new(inner))
+                }
+                _ => crate::unexpected_token!(crate::ast::TokenKind::None, p.Token.clone()),
+            }
+        }))?
+    }
+}
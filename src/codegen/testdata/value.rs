@@ -0,0 +1,181 @@
+// Code generated by proc-parser. DO NOT EDIT.
+
+crate::def_ast! {
+    Program {
+        Stmts: crate::ast::List<Stmt>,
+    },
+
+    Stmt {
+        Name: crate::ast::Token,
+        Value: Value,
+        Tag: crate::ast::Optional<crate::ast::Token>,
+        Ref: crate::ast::Optional<Box<Ref>>,
+    },
+
+    ValueLbrace {
+        Key: crate::ast::Token,
+        Value: Value,
+    },
+
+    Call {
+        Name: crate::ast::Token,
+        Args: crate::ast::List<Value>,
+    },
+
+    Ref {
+        Name: crate::ast::Token,
+        Target: crate::ast::Token,
+        Member: crate::ast::Optional<crate::ast::Token>,
+    }
+}
+
+pub enum Value {
+    None,
+    Call(Box<Call>),
+    Ref(Box<crate::ast::Token>),
+    Int(Box<crate::ast::Token>),
+    Items(Box<crate::ast::List<Value>>),
+    Names(Box<crate::ast::List<crate::ast::Token>>),
+    Lbrace(Box<ValueLbrace>),
+    Words(Box<crate::ast::List<crate::ast::Token>>),
+    Dot,
+}
+
+impl Default for Value {
+    fn default() -> Self { Value::None }
+}
+
+crate::def_parser! {
+    Program, p => {
+        let begin = p.Token.Pos.Begin;
+        let stmts = crate::ast::List::<Stmt>::Expect(p, crate::ast::TokenKind::SEMICOLON, crate::ast::TokenKind::EOF)?;
+
+        Program {
+            Stmts: stmts,
+            Pos: p.GetRange(begin),
+        }
+    },
+
+    Stmt, p => {
+        let begin = p.Token.Pos.Begin;
+        let mut tag = crate::ast::Optional::None;
+        let mut ref_ = crate::ast::Optional::None;
+        let name = { p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan()? };
+        p.MatchAndScan(crate::ast::TokenKind::COLON)?;
+        let value = Value::Expect(p)?;
+        match p.Token.Kind {
+            crate::ast::TokenKind::DOT => {
+                p.MatchAndScan(crate::ast::TokenKind::DOT)?;
+                tag = crate::ast::Optional::Some({ p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan()? });
+            }
+            crate::ast::TokenKind::COLON => {
+                p.MatchAndScan(crate::ast::TokenKind::COLON)?;
+                ref_ = crate::ast::Optional::Some(Box::new(Ref::Expect(p)?));
+            }
+            crate::ast::TokenKind::SEMICOLON => {}
+            crate::ast::TokenKind::EOF => {}
+            _ => crate::unexpected_token!(crate::ast::TokenKind::None, p.Token.clone()),
+        }
+
+        Stmt {
+            Name: name,
+            Value: value,
+            Tag: tag,
+            Ref: ref_,
+            Pos: p.GetRange(begin),
+        }
+    },
+
+    ValueLbrace, p => {
+        let begin = p.Token.Pos.Begin;
+        p.MatchAndScan(crate::ast::TokenKind::LBRACE)?;
+        let key = { p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan()? };
+        p.MatchAndScan(crate::ast::TokenKind::COLON)?;
+        let value = Value::Expect(p)?;
+        p.MatchAndScan(crate::ast::TokenKind::RBRACE)?;
+
+        ValueLbrace {
+            Key: key,
+            Value: value,
+            Pos: p.GetRange(begin),
+        }
+    },
+
+    Value, p => {
+        match (p.Token.Kind.clone(), p.Peek(1)?.Kind.clone()) {
+            (crate::ast::TokenKind::Ident, crate::ast::TokenKind::LPAREN) => {
+                let call = Box::new(Call::Expect(p)?);
+                Value::Call(call)
+            }
+            (crate::ast::TokenKind::Ident, _) => {
+                let ref_ = { p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan()? };
+                Value::Ref(Box::new(ref_))
+            }
+            (crate::ast::TokenKind::Int(_), _) => {
+                let int = { p.Match(crate::ast::TokenKind::Int(crate::scanner::IntFormat::DEC))?; p.GetTokenAndScan()? };
+                Value::Int(Box::new(int))
+            }
+            (crate::ast::TokenKind::LPAREN, _) => {
+                p.MatchAndScan(crate::ast::TokenKind::LPAREN)?;
+                let items = crate::ast::List::<Value>::Expect(p, crate::ast::TokenKind::COMMA, crate::ast::TokenKind::RPAREN)?;
+                p.MatchAndScan(crate::ast::TokenKind::RPAREN)?;
+                Value::Items(Box::new(items))
+            }
+            (crate::ast::TokenKind::LBRACK, _) => {
+                p.MatchAndScan(crate::ast::TokenKind::LBRACK)?;
+                let names = crate::ast::List::ExpectWith(p, crate::ast::TokenKind::COMMA, crate::ast::TokenKind::RBRACK, |p| { p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan() })?;
+                p.MatchAndScan(crate::ast::TokenKind::RBRACK)?;
+                Value::Names(Box::new(names))
+            }
+            (crate::ast::TokenKind::LBRACE, _) => {
+                Value::Lbrace(Box::new(ValueLbrace::Expect(p)?))
+            }
+            (crate::ast::TokenKind::COLON, _) => {
+                p.MatchAndScan(crate::ast::TokenKind::COLON)?;
+                let words = crate::ast::List::<crate::ast::Token>::Repeat(p, crate::ast::TokenKind::None, false, |token| matches!(token.Kind, crate::ast::TokenKind::Ident), |p| { p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan() })?;
+                Value::Words(Box::new(words))
+            }
+            (crate::ast::TokenKind::DOT, _) => {
+                p.MatchAndScan(crate::ast::TokenKind::DOT)?;
+                Value::Dot
+            }
+            _ => crate::unexpected_token!(crate::ast::TokenKind::None, p.Token.clone()),
+        }
+    },
+
+    Call, p => {
+        let begin = p.Token.Pos.Begin;
+        let name = { p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan()? };
+        p.MatchAndScan(crate::ast::TokenKind::LPAREN)?;
+        let args = crate::ast::List::<Value>::Repeat(p, crate::ast::TokenKind::COMMA, true, |token| matches!(token.Kind, crate::ast::TokenKind::COLON | crate::ast::TokenKind::DOT | crate::ast::TokenKind::Ident | crate::ast::TokenKind::Int(_) | crate::ast::TokenKind::LBRACE | crate::ast::TokenKind::LBRACK | crate::ast::TokenKind::LPAREN), Value::Expect)?;
+        p.MatchAndScan(crate::ast::TokenKind::RPAREN)?;
+
+        Call {
+            Name: name,
+            Args: args,
+            Pos: p.GetRange(begin),
+        }
+    },
+
+    Ref, p => {
+        let begin = p.Token.Pos.Begin;
+        let mut member = crate::ast::Optional::None;
+        let name = { p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan()? };
+        p.MatchAndScan(crate::ast::TokenKind::DOT)?;
+        let target = { p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan()? };
+        match p.Token.Kind {
+            crate::ast::TokenKind::DOT => {
+                p.MatchAndScan(crate::ast::TokenKind::DOT)?;
+                member = crate::ast::Optional::Some({ p.Match(crate::ast::TokenKind::Ident)?; p.GetTokenAndScan()? });
+            }
+            _ => {}
+        }
+
+        Ref {
+            Name: name,
+            Target: target,
+            Member: member,
+            Pos: p.GetRange(begin),
+        }
+    }
+}
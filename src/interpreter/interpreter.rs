@@ -0,0 +1,324 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use err_rs::*;
+
+use crate::unexpected_token;
+use crate::analysis::*;
+use crate::ast::*;
+use crate::interpreter::*;
+use crate::parser::*;
+use crate::scanner::*;
+
+#[derive(Debug)]
+pub enum GrammarError {
+    Syntax(ParserError),
+    Resolve(Vec<ResolveError>),
+    Recursion(Vec<RecursionError>),
+    Empty,
+}
+
+// A .ppg grammar ready to parse input without generating code.
+//
+// File has its templates expanded, see ExpandTemplates.
+pub struct Grammar {
+    pub File: File,
+    pub Sets: GrammarSets,
+}
+
+impl Grammar {
+    pub fn new(file: File) -> Result<Grammar, GrammarError> {
+        let file = wrap_result!(GrammarError::Resolve, ExpandTemplates(&file));
+        let symbols = wrap_result!(GrammarError::Resolve, Symbols::Resolve(&file));
+        if symbols.Start.is_none() {
+            err!(GrammarError::Empty);
+        }
+
+        let sets = GrammarSets::Compute(&file, &symbols);
+
+        // Left recursion would overflow the stack instead of failing.
+        let recursion = sets.CheckRecursion(&file);
+        if !recursion.is_empty() {
+            err!(GrammarError::Recursion(recursion));
+        }
+
+        Ok(Grammar {
+            File: file,
+            Sets: sets,
+        })
+    }
+
+    pub fn Load(src: &str) -> Result<Grammar, GrammarError> {
+        let mut p = File::NewParser(src);
+        wrap_result!(GrammarError::Syntax, p.Scan());
+        let file = wrap_result!(GrammarError::Syntax, File::Expect(&mut p));
+        Grammar::new(file)
+    }
+
+    // A parser over the input scanning the tokens the grammar declares, and char literals if it names Char.
+    pub fn NewParser(&self, input: &str) -> Parser {
+        let mut p = Parser::new(input.chars().collect());
+        p.KeywordLookup = self.Sets.Symbols.KeywordLookup();
+        p.Scanner.CharLiterals = self.Sets.Symbols.CharLiterals;
+        p
+    }
+
+    // Parses the whole input, which fails on a literal out of range as well, see BasicScanner.Overflows.
+    pub fn Parse(&self, input: &str) -> Result<Tree, ParserError> {
+        let mut p = self.NewParser(input);
+        p.Scan()?;
+        let tree = self.Expect(&mut p)?;
+        match p.Scanner.Overflows.into_iter().next() {
+            Some(e) => err!(ParserError::Overflow(e)),
+            None => Ok(tree)
+        }
+    }
+
+    // Parses the start rule beginning at the current token of the parser.
+    pub fn Expect(&self, p: &mut Parser) -> Result<Tree, ParserError> {
+        let start = self.Sets.Symbols.Start.expect("checked by Grammar::new");
+        Interpreter { Grammar: self }.Rule(p, start)
+    }
+
+    // Whether the tokens from the current one on match the names before `=>`, one token each,
+    // and satisfy the predicates on them.
+    pub fn PatternLookahead(&self, pattern: &Pattern, p: &mut Parser) -> Result<bool, ParserError> {
+        for (n, ahead) in pattern.AheadTokens().iter().enumerate() {
+            let token = p.Peek(n)?;
+            if let Some(name) = ahead.Name {
+                if !self.Lookahead(&name.Token.Literal, token) {
+                    return Ok(false);
+                }
+            }
+            if !ahead.Predicates.iter().all(|predicate| self.Holds(predicate, token)) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    // Whether the token satisfies `&X` or `!X`.
+    pub fn Holds(&self, predicate: &Predicate, token: &Token) -> bool {
+        self.Lookahead(&predicate.Rule.Token.Literal, token) != predicate.IsNot()
+    }
+
+    // Whether the token can start the rule or token named.
+    pub fn Lookahead(&self, name: &str, token: &Token) -> bool {
+        match self.Sets.Symbols.Lookup(name) {
+            Some(Symbol::Rule(index)) => {
+                let first = &self.Sets.First[index];
+                first.contains(token.Kind.Name()) || first.contains(WILDCARD)
+            }
+            Some(Symbol::Token(kind)) => token.Kind.Is(&kind),
+            Some(Symbol::Any) => true,
+            None => false
+        }
+    }
+
+    fn Want(&self, name: &str) -> TokenKind {
+        match self.Sets.Symbols.Lookup(name) {
+            Some(Symbol::Token(kind)) => kind,
+            _ => TokenKind::None
+        }
+    }
+}
+
+struct Interpreter<'a> {
+    Grammar: &'a Grammar,
+}
+
+impl<'a> Interpreter<'a> {
+    fn Rule(&mut self, p: &mut Parser, index: usize) -> Result<Tree, ParserError> {
+        let def: &'a Def = &self.Grammar.File.Definitions.Elements[index];
+        match def.Annotation("name").and_then(Annotation::Text) {
+            Some(name) => p.Expecting(name, |p| self.Def(p, def)),
+            None => self.Def(p, def),
+        }
+    }
+
+    fn Def(&mut self, p: &mut Parser, def: &'a Def) -> Result<Tree, ParserError> {
+        let begin = p.Token.Pos.Begin;
+
+        if let [Node::Precedence(precedence)] = &def.Rule.Elements[..] {
+            return self.Climb(p, &def.Name.Token.Literal, precedence, 1);
+        }
+
+        let mut node = TreeNode {
+            Pos: PosRange::default(),
+            Name: def.Name.Token.Literal.clone(),
+            Fields: vec![],
+        };
+        self.Sequence(p, &def.Rule.Elements, &mut node)?;
+        node.Pos = p.GetRange(begin);
+
+        Ok(Tree::Node(Box::new(node)))
+    }
+
+    fn Symbol(&mut self, p: &mut Parser, name: &str) -> Result<Tree, ParserError> {
+        match self.Grammar.Sets.Symbols.Lookup(name) {
+            Some(Symbol::Rule(index)) => self.Rule(p, index),
+            Some(Symbol::Token(kind)) => {
+                p.Match(kind)?;
+                Ok(Tree::Token(p.GetTokenAndScan()?))
+            }
+            Some(Symbol::Any) => Ok(Tree::Token(p.GetTokenAndScan()?)),
+            None => unreachable!("names are resolved by Grammar::new")
+        }
+    }
+
+    // Level of the first prefix or binary operator level holding the token, counting from 1.
+    fn Level(&self, precedence: &'a PrecedenceRule, prefix: bool, token: &Token) -> Option<(usize, &'a PrecedenceLevel)> {
+        precedence.Levels.Elements.iter().enumerate()
+            .filter(|(_, level)| level.IsPrefix() == prefix)
+            .find(|(_, level)| level.Operators.iter().any(|operator| self.Grammar.Lookahead(&operator.Token.Literal, token)))
+            .map(|(index, level)| (index + 1, level))
+    }
+
+    // Precedence climbing, taking binary operators of at least the minimum level.
+    //
+    // Operands are the trees of the operand rule, operators produce nodes named after
+    // the definition with `left`, `op` and `right` fields, or `op` and `operand` for prefixes.
+    fn Climb(&mut self, p: &mut Parser, name: &str, precedence: &'a PrecedenceRule, min: usize) -> Result<Tree, ParserError> {
+        let begin = p.Token.Pos.Begin;
+
+        let mut left = match self.Level(precedence, true, &p.Token) {
+            Some((level, _)) => {
+                let op = p.GetTokenAndScan()?;
+                let operand = self.Climb(p, name, precedence, level)?;
+                Tree::Node(Box::new(TreeNode {
+                    Pos: p.GetRange(begin),
+                    Name: name.to_string(),
+                    Fields: vec![("op".to_string(), Tree::Token(op)), ("operand".to_string(), operand)],
+                }))
+            }
+            None => self.Symbol(p, &precedence.Operand.Token.Literal)?,
+        };
+
+        while let Some((level, assoc)) = self.Level(precedence, false, &p.Token) {
+            if level < min {
+                break;
+            }
+            let op = p.GetTokenAndScan()?;
+            let right = self.Climb(p, name, precedence, if assoc.IsRight() { level } else { level + 1 })?;
+            left = Tree::Node(Box::new(TreeNode {
+                Pos: p.GetRange(begin),
+                Name: name.to_string(),
+                Fields: vec![("left".to_string(), left), ("op".to_string(), Tree::Token(op)), ("right".to_string(), right)],
+            }));
+        }
+        Ok(left)
+    }
+
+    fn Sequence(&mut self, p: &mut Parser, nodes: &'a [Node], node: &mut TreeNode) -> Result<(), ParserError> {
+        for rule in nodes {
+            match rule {
+                Node::None => {}
+                Node::Ident(ident) => { self.Symbol(p, &ident.Token.Literal)?; }
+                Node::Field(field) => {
+                    let value = self.Symbol(p, &field.Rule.Token.Literal)?;
+                    node.Fields.push((field.Name.Token.Literal.clone(), value));
+                }
+                Node::Match(branch) => {
+                    // The first pattern accepting the lookahead wins, like Node::Expect.
+                    let mut pattern = None;
+                    for candidate in &branch.Patterns.Elements {
+                        if self.Grammar.PatternLookahead(candidate, p)? {
+                            pattern = Some(candidate);
+                            break;
+                        }
+                    }
+                    match pattern {
+                        Some(pattern) => self.Sequence(p, &pattern.Rule.Elements, node)?,
+                        None => unexpected_token!(TokenKind::None, p.Token.clone())
+                    }
+                }
+                Node::ListRule(list) => {
+                    let value = self.List(p, list)?;
+                    node.Fields.push((list.Field.Name.Token.Literal.clone(), value));
+                }
+                Node::Repeat(repeat) => {
+                    let value = self.Repeat(p, repeat)?;
+                    node.Fields.push((repeat.Field.Name.Token.Literal.clone(), value));
+                }
+                Node::Precedence(_) => unreachable!("precedence rules are checked by Grammar::new"),
+                Node::Predicate(predicate) => if !self.Grammar.Holds(predicate, &p.Token) {
+                    let want = if predicate.IsNot() { TokenKind::None } else { self.Grammar.Want(&predicate.Rule.Token.Literal) };
+                    unexpected_token!(want, p.Token.clone());
+                }
+                Node::Optional(optional) => {
+                    // Skipped unless the lookahead can start it, its fields are then absent.
+                    let first = self.Grammar.Sets.SequenceFirst(&optional.Rule.Elements).0;
+                    if first.contains(p.Token.Kind.Name()) || first.contains(WILDCARD) {
+                        self.Sequence(p, &optional.Rule.Elements, node)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Same as List::Expect, the terminator is not consumed.
+    fn List(&mut self, p: &mut Parser, list: &'a ListRule) -> Result<Tree, ParserError> {
+        let grammar = self.Grammar;
+        let begin = p.Token.Pos.Begin;
+        let delimiter = &list.Delimiter.Token.Literal;
+        let terminator = &list.Term.Token.Literal;
+        let mut elements: Vec<Tree> = vec![];
+
+        loop {
+            if grammar.Lookahead(terminator, &p.Token) {
+                break;
+            }
+            elements.push(self.Symbol(p, &list.Field.Rule.Token.Literal)?);
+            if grammar.Lookahead(delimiter, &p.Token) {
+                self.Symbol(p, delimiter)?;
+            } else {
+                if !grammar.Lookahead(terminator, &p.Token) {
+                    unexpected_token!(grammar.Want(terminator), p.Token.clone());
+                }
+                break;
+            }
+        }
+
+        Ok(Tree::List(List {
+            Pos: p.GetRange(begin),
+            Elements: elements,
+            Delimiter: grammar.Want(delimiter),
+            Term: grammar.Want(terminator),
+        }))
+    }
+
+    // Same as List::Repeat.
+    fn Repeat(&mut self, p: &mut Parser, repeat: &'a RepeatRule) -> Result<Tree, ParserError> {
+        let grammar = self.Grammar;
+        let begin = p.Token.Pos.Begin;
+        let element = &repeat.Field.Rule.Token.Literal;
+        let mut elements: Vec<Tree> = vec![];
+
+        if !repeat.MayBeEmpty() || grammar.Lookahead(element, &p.Token) {
+            loop {
+                elements.push(self.Symbol(p, element)?);
+                if let Optional::Some(delimiter) = &repeat.Delimiter {
+                    if !grammar.Lookahead(&delimiter.Token.Literal, &p.Token) {
+                        break;
+                    }
+                    self.Symbol(p, &delimiter.Token.Literal)?;
+                }
+                if !grammar.Lookahead(element, &p.Token) {
+                    break;
+                }
+            }
+        }
+
+        Ok(Tree::List(List {
+            Pos: p.GetRange(begin),
+            Elements: elements,
+            Delimiter: match &repeat.Delimiter {
+                Optional::Some(delimiter) => grammar.Want(&delimiter.Token.Literal),
+                Optional::None => TokenKind::None,
+            },
+            Term: TokenKind::None,
+        }))
+    }
+}
@@ -0,0 +1,377 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::fs;
+
+use crate::ast::{META_SEMICOLON_AFTER, TokenKind};
+use crate::interpreter::*;
+use crate::parser::ParserError;
+use crate::scanner::{BasicScannerError, IntFormat};
+
+const GRAMMAR: &str = r#"start Program
+
+Program := ($stmts:Stmt, SEMICOLON, EOF)
+
+Stmt := $name:Ident, COLON, $value:Value
+
+Value := {
+    Ident => $ref:Ident
+    Int => $int:Int
+    LPAREN => LPAREN, ($items:Value, COMMA, RPAREN), RPAREN
+}
+"#;
+
+fn node(tree: &Tree) -> &TreeNode {
+    match tree {
+        Tree::Node(node) => node,
+        _ => panic!("not a node")
+    }
+}
+
+fn list(tree: &Tree) -> &Vec<Tree> {
+    match tree {
+        Tree::List(list) => &list.Elements,
+        _ => panic!("not a list")
+    }
+}
+
+fn token(tree: &Tree) -> &str {
+    match tree {
+        Tree::Token(token) => &token.Literal,
+        _ => panic!("not a token")
+    }
+}
+
+#[test]
+fn TestGrammar_Parse() {
+    let grammar = Grammar::Load(GRAMMAR).unwrap();
+
+    let tree = grammar.Parse("a: 1\nb: (x, (2))\n").unwrap();
+
+    let program = node(&tree);
+    assert_eq!(program.Name, "Program");
+
+    let stmts = list(program.Get("stmts").unwrap());
+    assert_eq!(stmts.len(), 2);
+
+    let a = node(&stmts[0]);
+    assert_eq!(token(a.Get("name").unwrap()), "a");
+    assert_eq!(token(node(a.Get("value").unwrap()).Get("int").unwrap()), "1");
+    assert_eq!((a.Pos.Begin.Offset, a.Pos.End.Offset), (0, 4));
+
+    let b = node(&stmts[1]);
+    assert_eq!((b.Pos.Begin.Line, b.Pos.Begin.Column, b.Pos.End.Column), (1, 0, 11));
+    let items = list(node(b.Get("value").unwrap()).Get("items").unwrap());
+    assert_eq!(token(node(&items[0]).Get("ref").unwrap()), "x");
+    assert_eq!(list(node(&items[1]).Get("items").unwrap()).len(), 1);
+}
+
+#[test]
+fn TestGrammar_ParseError() {
+    let grammar = Grammar::Load(GRAMMAR).unwrap();
+
+    match grammar.Parse("a 1\n") {
+        Err(ParserError::UnexpectedToken(e)) => assert_eq!(e.Have.Literal, "1"),
+        _ => panic!()
+    }
+}
+
+#[test]
+fn TestGrammar_Overflow() {
+    let grammar = Grammar::Load(GRAMMAR).unwrap();
+
+    // The input parses, but not its literal.
+    match grammar.Parse("a: 255u8\nb: 256u8\n") {
+        Err(ParserError::Overflow(e)) => {
+            assert_eq!(e.Type, "u8");
+            assert_eq!((e.PosRange.Begin.Line, e.PosRange.Begin.Column, e.PosRange.End.Column), (1, 3, 8));
+        }
+        _ => panic!()
+    }
+}
+
+#[test]
+fn TestGrammar_Load() {
+    assert!(matches!(Grammar::Load("File := Missing\n"), Err(GrammarError::Resolve(_))));
+    assert!(matches!(Grammar::Load("File := $f:File, DOT\n"), Err(GrammarError::Recursion(_))));
+    assert!(matches!(Grammar::Load("File := :=\n"), Err(GrammarError::Syntax(_))));
+}
+
+#[test]
+fn TestGrammar_Optional() {
+    let grammar = Grammar::Load("Program := ($stmts:Stmt, SEMICOLON, EOF)\nStmt := $name:Ident, [COLON, $value:Int]\n").unwrap();
+
+    let tree = grammar.Parse("a: 1\nb\n").unwrap();
+
+    let stmts = list(node(&tree).Get("stmts").unwrap());
+    assert_eq!(token(node(&stmts[0]).Get("value").unwrap()), "1");
+    assert!(node(&stmts[1]).Get("value").is_none());
+}
+
+#[test]
+fn TestGrammar_Repeat() {
+    let grammar = Grammar::Load(r#"File := ($stmts:Stmt+)
+Stmt := $name:Ident, LPAREN, ($args:Int*, COMMA), RPAREN, SEMICOLON
+"#).unwrap();
+
+    let tree = grammar.Parse("a(1, 2,)\nb()\nc(3)\n").unwrap();
+
+    let stmts = list(node(&tree).Get("stmts").unwrap());
+    assert_eq!(stmts.len(), 3);
+    assert_eq!(list(node(&stmts[0]).Get("args").unwrap()).len(), 2);
+    assert!(list(node(&stmts[1]).Get("args").unwrap()).is_empty());
+    assert_eq!(token(&list(node(&stmts[2]).Get("args").unwrap())[0]), "3");
+
+    assert!(grammar.Parse("").is_err());
+    assert!(grammar.Parse("a(1 2)\n").is_err());
+}
+
+#[test]
+fn TestGrammar_Lookahead() {
+    let grammar = Grammar::Load(r#"File := ($stmts:Stmt, SEMICOLON, EOF)
+Stmt := {
+    Ident, COLON => $label:Ident, COLON, $next:Stmt
+    Ident, LPAREN => $call:Ident, LPAREN, RPAREN
+    Ident => $ref:Ident
+}
+"#).unwrap();
+
+    let tree = grammar.Parse("a: b()\nc\n").unwrap();
+
+    let stmts = list(node(&tree).Get("stmts").unwrap());
+    let a = node(&stmts[0]);
+    assert_eq!(token(a.Get("label").unwrap()), "a");
+    assert_eq!(token(node(a.Get("next").unwrap()).Get("call").unwrap()), "b");
+    assert_eq!(token(node(&stmts[1]).Get("ref").unwrap()), "c");
+    assert_eq!((a.Pos.Begin.Offset, a.Pos.End.Offset), (0, 6));
+}
+
+#[test]
+fn TestGrammar_Predicates() {
+    let grammar = Grammar::Load(r#"File := ($stmts:Stmt, SEMICOLON, EOF)
+Stmt := {
+    Ident, !LSS => $ref:Ident
+    Ident => $generic:Ident, LSS, $arg:Ident, GTR, !Ident
+    LPAREN => LPAREN, &Int, ($items:Int, COMMA, RPAREN), RPAREN
+}
+"#).unwrap();
+
+    // `>` does not end a line by default.
+    let tree = grammar.Parse("a\nb<c>;\n(1, 2)\n").unwrap();
+
+    let stmts = list(node(&tree).Get("stmts").unwrap());
+    assert_eq!(token(node(&stmts[0]).Get("ref").unwrap()), "a");
+    assert_eq!(token(node(&stmts[1]).Get("arg").unwrap()), "c");
+    assert_eq!(list(node(&stmts[2]).Get("items").unwrap()).len(), 2);
+
+    for (input, want, have) in [("b<c> d\n", TokenKind::None, "d"), ("()\n", TokenKind::Int(IntFormat::DEC), ")")] {
+        match grammar.Parse(input) {
+            Err(ParserError::UnexpectedToken(e)) => {
+                assert!(e.Want.Is(&want));
+                assert_eq!(e.Have.Literal, have);
+            }
+            _ => panic!()
+        }
+    }
+}
+
+#[test]
+fn TestGrammar_Default() {
+    let grammar = Grammar::Load(r#"File := ($stmts:Stmt, SEMICOLON, EOF)
+Stmt := $name:Ident, $value:Value
+Value := {
+    COLON => COLON, $int:Int
+    LPAREN => LPAREN, $ref:Ident, RPAREN
+    _ => ;
+}
+"#).unwrap();
+
+    let tree = grammar.Parse("a: 1\nb\nc(d)\n").unwrap();
+
+    let stmts = list(node(&tree).Get("stmts").unwrap());
+    assert_eq!(token(node(node(&stmts[0]).Get("value").unwrap()).Get("int").unwrap()), "1");
+    assert!(node(node(&stmts[1]).Get("value").unwrap()).Fields.is_empty());
+    assert_eq!(token(node(node(&stmts[2]).Get("value").unwrap()).Get("ref").unwrap()), "d");
+
+    // Taken for a token no other pattern starts with, leaving it to what follows.
+    match grammar.Parse("a 1\n") {
+        Err(ParserError::UnexpectedToken(e)) => assert_eq!(e.Have.Literal, "1"),
+        _ => panic!()
+    }
+}
+
+#[test]
+fn TestGrammar_Tokens() {
+    let grammar = Grammar::Load(r#"token ASSIGN ":="
+keyword LET "let"
+
+File := ($stmts:Stmt, SEMICOLON, EOF)
+Stmt := LET, $name:Ident, ASSIGN, $value:Int
+"#).unwrap();
+
+    let tree = grammar.Parse("let a := 1\nlet b := 2\n").unwrap();
+
+    let stmts = list(node(&tree).Get("stmts").unwrap());
+    assert_eq!(stmts.len(), 2);
+    assert_eq!(token(node(&stmts[1]).Get("name").unwrap()), "b");
+
+    // `=>` is not declared and scanned as a plain operator.
+    assert!(grammar.Parse("let a => 1\n").is_err());
+}
+
+#[test]
+fn TestGrammar_CharLiterals() {
+    // A grammar naming Char scans single quotes as char literals.
+    let grammar = Grammar::Load("File := ($items:Item, COMMA, EOF)\nItem := { Char => $char:Char; String => $string:String; }\n").unwrap();
+
+    let tree = grammar.Parse("'a', \"bc\", '\\n'\n").unwrap();
+
+    let items: Vec<(&str, &str)> = list(node(&tree).Get("items").unwrap()).iter()
+        .map(|item| node(item).Fields.first().map(|(name, tree)| (name.as_str(), token(tree))).unwrap())
+        .collect();
+    assert_eq!(items, vec![("char", "a"), ("string", "bc"), ("char", "\n")]);
+    assert!(matches!(grammar.Parse("'bc'\n"), Err(ParserError::ScannerError(BasicScannerError::BadChar(_)))));
+
+    // Others keep scanning them as strings.
+    let grammar = Grammar::Load("File := ($items:String, COMMA, EOF)\n").unwrap();
+    let tree = grammar.Parse("'bc', \"d\"\n").unwrap();
+    assert_eq!(list(node(&tree).Get("items").unwrap()).iter().map(token).collect::<Vec<_>>(), vec!["bc", "d"]);
+}
+
+#[test]
+fn TestGrammar_Precedence() {
+    let grammar = Grammar::Load(r#"token PLUS "+"
+token MINUS "-"
+token STAR "*"
+token CARET "^"
+
+File := ($exprs:Expr, SEMICOLON, EOF)
+Expr := precedence Ident { left PLUS MINUS; left STAR; prefix MINUS; right CARET; }
+"#).unwrap();
+
+    let tree = grammar.Parse("a - b - c * - d ^ e ^ f\n").unwrap();
+
+    // (a - b) - (c * (-(d ^ (e ^ f))))
+    let sub = node(&list(node(&tree).Get("exprs").unwrap())[0]);
+    assert_eq!(sub.Name, "Expr");
+    assert_eq!((sub.Pos.Begin.Offset, sub.Pos.End.Offset), (0, 23));
+    assert_eq!(token(node(sub.Get("left").unwrap()).Get("left").unwrap()), "a");
+    let mul = node(sub.Get("right").unwrap());
+    assert_eq!(token(mul.Get("op").unwrap()), "*");
+    let neg = node(mul.Get("right").unwrap());
+    assert_eq!((neg.Pos.Begin.Offset, neg.Pos.End.Offset), (12, 23));
+    let pow = node(neg.Get("operand").unwrap());
+    assert_eq!(token(pow.Get("left").unwrap()), "d");
+    assert_eq!(token(node(pow.Get("right").unwrap()).Get("left").unwrap()), "e");
+}
+
+#[test]
+fn TestGrammar_Literals() {
+    let grammar = Grammar::Load(r#"File := ($stmts:Stmt, SEMICOLON, EOF)
+Stmt := { "let" => "let", $name:Ident, ":=", $value:Int; "fn" => "fn", $name:Ident, "(", ")"; }
+"#).unwrap();
+
+    let tree = grammar.Parse("let a := 1\nfn f()\n").unwrap();
+
+    let stmts = list(node(&tree).Get("stmts").unwrap());
+    assert_eq!(token(node(&stmts[0]).Get("value").unwrap()), "1");
+    assert_eq!(token(node(&stmts[1]).Get("name").unwrap()), "f");
+
+    // Quoted keywords are no longer identifiers.
+    match grammar.Parse("let fn := 1\n") {
+        Err(ParserError::UnexpectedToken(e)) => assert_eq!(e.Have.Kind.to_string(), "\"fn\""),
+        _ => panic!()
+    }
+}
+
+#[test]
+fn TestGrammar_Annotations() {
+    let grammar = Grammar::Load(r#"File := ($stmts:Stmt, SEMICOLON, EOF)
+@name("statement") @inline @recover(SEMICOLON)
+Stmt := $name:Ident, "=", $value:Value
+@name("value")
+Value := { Int => $int:Int; String => $string:String; }
+"#).unwrap();
+
+    // Named after the innermost rule starting at the unexpected token.
+    for (input, want) in [("a = )\n", "value"), ("1\n", "statement")] {
+        match grammar.Parse(input) {
+            Err(ParserError::Expected(e)) => assert_eq!(e.Name, want),
+            _ => panic!()
+        }
+    }
+
+    // Past the start of the rule, errors are left as they are.
+    assert!(matches!(grammar.Parse("a 1\n"), Err(ParserError::UnexpectedToken(_))));
+
+    // Hints do not recover from errors.
+    assert!(matches!(grammar.Parse("a = 1\nb 1\nc = 2\n"), Err(ParserError::UnexpectedToken(_))));
+    assert_eq!(list(node(&grammar.Parse("a = 1\nc = 2\n").unwrap()).Get("stmts").unwrap()).len(), 2);
+}
+
+#[test]
+fn TestGrammar_Templates() {
+    let grammar = Grammar::Load(r#"File := ($lists:Parens<Commas<Ident>>, SEMICOLON, EOF)
+Parens<T> := LPAREN, $inner:T, RPAREN
+Commas<T> := ($items:T*, COMMA)
+"#).unwrap();
+
+    let tree = grammar.Parse("(a, b)\n()\n").unwrap();
+
+    let lists = list(node(&tree).Get("lists").unwrap());
+    assert_eq!(lists.len(), 2);
+    assert_eq!(node(&lists[0]).Name, "ParensCommasIdent");
+    let items = list(node(node(&lists[0]).Get("inner").unwrap()).Get("items").unwrap());
+    assert_eq!(items.iter().map(token).collect::<Vec<_>>(), vec!["a", "b"]);
+}
+
+#[test]
+fn TestGrammar_MetaGrammar() {
+    let src = fs::read_to_string("proc-parser.ppg").unwrap();
+    let grammar = Grammar::Load(&src).unwrap();
+
+    let mut p = grammar.NewParser(&src);
+    p.SemicolonAfter.extend_from_slice(META_SEMICOLON_AFTER);
+    p.Scan().unwrap();
+    let tree = grammar.Expect(&mut p).unwrap();
+
+    let file = node(&tree);
+    let definitions = list(file.Get("definitions").unwrap());
+    assert_eq!(definitions.len(), grammar.File.Definitions.Elements.len());
+    assert_eq!(token(node(&definitions[0]).Get("name").unwrap()), "Field");
+    assert_eq!(token(node(file.Get("header").unwrap()).Get("rule").unwrap()), "File");
+}
+
+#[test]
+fn TestGrammar_MetaKeywords() {
+    let grammar = Grammar::Load(GRAMMAR).unwrap();
+
+    // Only the meta-grammar scans these as keywords.
+    let tree = grammar.Parse("token: start\nimport: (keyword, precedence)\n").unwrap();
+
+    let stmts = list(node(&tree).Get("stmts").unwrap());
+    assert_eq!(token(node(&stmts[0]).Get("name").unwrap()), "token");
+    assert_eq!(token(node(node(&stmts[0]).Get("value").unwrap()).Get("ref").unwrap()), "start");
+    assert_eq!(token(node(&stmts[1]).Get("name").unwrap()), "import");
+}
+
+#[test]
+fn TestTree_Display() {
+    let grammar = Grammar::Load(GRAMMAR).unwrap();
+
+    let tree = grammar.Parse("a: (x)\n").unwrap();
+
+    let text = tree.to_string();
+    println!("{}", text);
+    assert_eq!(text.lines().collect::<Vec<&str>>(), vec![
+        "Program 1:1 -> 2:1",
+        "    stmts: [1] 1:1 -> 2:1",
+        "        - Stmt 1:1 -> 1:7",
+        "            name: Ident \"a\" 1:1 -> 1:2",
+        "            value: Value 1:4 -> 1:7",
+        "                items: [1] 1:5 -> 1:6",
+        "                    - Value 1:5 -> 1:6",
+        "                        ref: Ident \"x\" 1:5 -> 1:6",
+    ]);
+}
@@ -0,0 +1,67 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use std::fmt;
+use std::fmt::Formatter;
+
+use crate::ast::*;
+use crate::scanner::*;
+
+// Parse tree produced by a Grammar instead of def_ast! types.
+pub enum Tree {
+    Token(Token),
+    Node(Box<TreeNode>),
+    List(List<Tree>),
+}
+
+impl Tree {
+    pub fn Pos(&self) -> PosRange {
+        match self {
+            Tree::Token(token) => token.Pos,
+            Tree::Node(node) => node.Pos,
+            Tree::List(list) => list.Pos,
+        }
+    }
+
+    fn Write(&self, f: &mut Formatter<'_>, indent: usize) -> fmt::Result {
+        match self {
+            Tree::Token(token) => writeln!(f, "{} {:?} {}", token.Kind.Name(), token.Literal, token.Pos),
+            Tree::Node(node) => {
+                writeln!(f, "{} {}", node.Name, node.Pos)?;
+                for (name, tree) in &node.Fields {
+                    write!(f, "{:indent$}{}: ", "", name, indent = indent + 4)?;
+                    tree.Write(f, indent + 4)?;
+                }
+                Ok(())
+            }
+            Tree::List(list) => {
+                writeln!(f, "[{}] {}", list.Elements.len(), list.Pos)?;
+                for tree in &list.Elements {
+                    write!(f, "{:indent$}- ", "", indent = indent + 4)?;
+                    tree.Write(f, indent + 4)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// One line per node, fields and list elements indented below it.
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { self.Write(f, 0) }
+}
+
+// Result of a definition, holding its fields in the order they were parsed.
+// Fields of branch patterns not taken and of skipped optional groups are absent.
+pub struct TreeNode {
+    pub Pos: PosRange,
+    pub Name: String,
+    pub Fields: Vec<(String, Tree)>,
+}
+
+impl TreeNode {
+    pub fn Get(&self, name: &str) -> Option<&Tree> {
+        self.Fields.iter().find(|(field, _)| field == name).map(|(_, tree)| tree)
+    }
+}
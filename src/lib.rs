@@ -8,6 +8,10 @@
 pub mod parser;
 pub mod scanner;
 pub mod ast;
+pub mod analysis;
+pub mod interpreter;
+pub mod codegen;
+pub mod build;
 
 #[macro_export]
 macro_rules! tag_matches {
@@ -12,6 +12,24 @@ pub struct UnexpectedTokenError {
     pub Have: Token,
 }
 
+impl fmt::Display for UnexpectedTokenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "unexpected token: want {} but have {} \"{}\"", self.Want, self.Have.Kind, self.Have.Literal) }
+}
+
 impl fmt::Debug for UnexpectedTokenError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} unexpected token: want {} but have {} \"{}\"", self.Have.Pos, self.Want, self.Have.Kind, self.Have.Literal) }
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Have.Pos, self) }
+}
+
+// An unexpected token where a rule annotated with `@name("...")` starts.
+pub struct ExpectedError {
+    pub Name: String,
+    pub Have: Token,
+}
+
+impl fmt::Display for ExpectedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "unexpected token: want {} but have {} \"{}\"", self.Name, self.Have.Kind, self.Have.Literal) }
+}
+
+impl fmt::Debug for ExpectedError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{} {}", self.Have.Pos, self) }
 }
@@ -6,12 +6,35 @@
 macro_rules! def_parser {
     (
         $(
+        $(#[$meta:meta])*
         $ast_node:ty, $p:ident => $block:block
         ), *
     ) => {
         $(
-        impl crate::parser::AstNodeParserTrait<$ast_node> for $ast_node {
-            fn Expect($p: &mut crate::parser::Parser) -> Result<$ast_node, ParserError> { Ok($block) }
+        impl $crate::parser::AstNodeParserTrait<$ast_node> for $ast_node {
+            $(#[$meta])*
+            fn Expect($p: &mut $crate::parser::Parser) -> Result<$ast_node, $crate::parser::ParserError> { Ok($block) }
+        }
+        )*
+    };
+}
+
+// AstNodeActionTrait for nodes parsed by any implementation of the actions trait,
+// `Node => Value, p, a => { ... }` producing the associated type Value of the implementation.
+#[macro_export]
+macro_rules! def_action {
+    (
+        $generic:ident: $actions:path;
+        $(
+        $(#[$meta:meta])*
+        $ast_node:ty => $value:ident, $p:ident, $a:ident => $block:block
+        ), *
+    ) => {
+        $(
+        impl<$generic: $actions> $crate::parser::AstNodeActionTrait<$generic> for $ast_node {
+            type Value = $generic::$value;
+            $(#[$meta])*
+            fn Act($p: &mut $crate::parser::Parser, $a: &mut $generic) -> Result<$generic::$value, $crate::parser::ParserError> { Ok($block) }
         }
         )*
     };
@@ -20,9 +43,9 @@ macro_rules! def_parser {
 #[macro_export]
 macro_rules! unexpected_token {
     ($want:expr, $have:expr) => {
-        err_rs::err!(crate::parser::ParserError::UnexpectedToken, crate::parser::UnexpectedTokenError {
+        return Err($crate::parser::ParserError::UnexpectedToken($crate::parser::UnexpectedTokenError {
             Want: $want,
             Have: $have,
-        })
+        }))
     }
 }
@@ -2,24 +2,46 @@
 // This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
 // that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 use err_rs::*;
 
 use crate::ast::{Token, TokenKind};
 use crate::parser::*;
 use crate::scanner::*;
-use crate::tag_matches;
 use crate::unexpected_token;
 
 pub trait AstNodeParserTrait<T> {
     fn Expect(p: &mut Parser) -> Result<T, ParserError>;
 }
 
+// Parses a node into a value built by the semantic actions A instead of the node itself,
+// see Generator.Actions.
+pub trait AstNodeActionTrait<A> {
+    type Value;
+    fn Act(p: &mut Parser, actions: &mut A) -> Result<Self::Value, ParserError>;
+}
+
 #[derive(Debug)]
 pub enum ParserError {
     ScannerError(BasicScannerError),
     UnexpectedToken(UnexpectedTokenError),
+    Expected(ExpectedError),
+    // A number literal out of range of its type, see BasicScanner.Overflows.
+    Overflow(OverflowError),
+}
+
+impl ParserError {
+    pub fn Pos(&self) -> PosRange {
+        match self {
+            ParserError::ScannerError(BasicScannerError::EOF(e)) => PosRange { Begin: e.Pos, End: e.Pos },
+            ParserError::ScannerError(BasicScannerError::BadFormat(e)) => e.PosRange,
+            ParserError::ScannerError(BasicScannerError::BadChar(e)) => e.PosRange,
+            ParserError::UnexpectedToken(e) => e.Have.Pos,
+            ParserError::Expected(e) => e.Have.Pos,
+            ParserError::Overflow(e) => e.PosRange,
+        }
+    }
 }
 
 pub struct Parser {
@@ -31,6 +53,15 @@ pub struct Parser {
 
     // Insert semicolon when true
     pub CompleteSemicolon: bool,
+    // Kinds of the tokens after which a newline completes a semicolon.
+    pub SemicolonAfter: Vec<TokenKind>,
+
+    // Tokens after Token scanned by Peek, with the position of the scanner after each.
+    Ahead: VecDeque<(Token, Position)>,
+    // Position of the scanner after Token, see GetPos.
+    Pos: Position,
+    // End of the token before Token, see GetEnd.
+    End: Position,
 }
 
 impl Parser {
@@ -40,23 +71,96 @@ impl Parser {
                 BufferScanner: BufferScanner::new(buffer),
                 Delimiters: vec!['(', ')', '[', ']', '{', '}', ',', ';', '/', '\n'],
                 Whitespaces: vec![' ', '\t', '\r'],
+                CharLiterals: false,
+                Overflows: vec![],
             },
             KeywordLookup: TokenKind::KeywordLookup(),
             Token: Token::default(),
 
             CompleteSemicolon: false,
+            SemicolonAfter: vec![TokenKind::Ident, TokenKind::Int(IntFormat::DEC), TokenKind::Float, TokenKind::RBRACE, TokenKind::RPAREN],
+
+            Ahead: VecDeque::new(),
+            Pos: Position::default(),
+            End: Position::default(),
+        }
+    }
+
+    pub fn GetPos(&self) -> Position {
+        match self.Ahead.is_empty() {
+            true => self.Scanner.GetPos(),
+            false => self.Pos
         }
     }
 
-    pub fn GetPos(&self) -> Position { self.Scanner.GetPos() }
+    // End of the last token scanned past, unlike GetPos not including the current token.
+    pub fn GetEnd(&self) -> Position { self.End }
+
+    // Range of a node from begin, the beginning of its first token, to the end of its last token scanned past,
+    // empty at begin if there is none.
+    //
+    // Generated parsers and the interpreter both range their nodes so.
+    pub fn GetRange(&self, begin: Position) -> PosRange {
+        match self.End.Offset < begin.Offset {
+            true => PosRange { Begin: begin, End: begin },
+            false => PosRange { Begin: begin, End: self.End }
+        }
+    }
 
     pub fn Scan(&mut self) -> Result<&Token, ParserError> {
+        self.End = self.Token.Pos.End;
+        match self.Ahead.pop_front() {
+            Some((token, pos)) => {
+                self.Token = token;
+                self.Pos = pos;
+            }
+            None => {
+                let previous = self.Token.clone();
+                self.Token = self.Next(&previous)?;
+                self.Pos = self.Scanner.GetPos();
+            }
+        }
+        Ok(&self.Token)
+    }
+
+    // The token n tokens after the current one without consuming any, Peek(0) is the current token.
+    //
+    // Past the end of input it is EOF.
+    pub fn Peek(&mut self, n: usize) -> Result<&Token, ParserError> {
+        while self.Ahead.len() < n {
+            let previous = self.Ahead.back().map_or(&self.Token, |(token, _)| token).clone();
+            let token = match previous.Kind {
+                TokenKind::EOF => previous,
+                _ => self.Next(&previous)?
+            };
+            self.Ahead.push_back((token, self.Scanner.GetPos()));
+        }
+        match n {
+            0 => Ok(&self.Token),
+            _ => Ok(&self.Ahead[n - 1].0)
+        }
+    }
+
+    // Scans the token after previous.
+    fn Next(&mut self, previous: &Token) -> Result<Token, ParserError> {
         let bt = on_err!(self.Scanner.Scan(), err => match err {
-            BasicScannerError::EOF(_) => match self.Token.Kind {
+            BasicScannerError::EOF(_) => match previous.Kind {
                 TokenKind::EOF => err!(ParserError::ScannerError(err)),
+                // The end of input ends a line as a newline does.
+                _ if self.CompleteSemicolon => {
+                    self.CompleteSemicolon = false;
+                    let pos = self.Scanner.GetPos();
+                    ok!(Token {
+                        Pos: PosRange { Begin: pos, End: pos },
+                        Kind: TokenKind::SEMICOLON,
+                        Literal: ";".to_string(),
+                        Number: None,
+                    });
+                }
                 _ => {
-                    self.Token.Kind = TokenKind::EOF;
-                    ok!(&self.Token);
+                    let mut token = previous.clone();
+                    token.Kind = TokenKind::EOF;
+                    ok!(token);
                 }
             }
             _ => err!(ParserError::ScannerError(err))
@@ -79,43 +183,94 @@ impl Parser {
                 }
             }
             BasicTokenKind::Delimiter => {
-                self.KeywordLookup.get(&literal).expect("implementation forgotten to add delimiter to the keyword lookup table").to_owned()
+                match literal.as_str() {
+                    "\n" => TokenKind::NEWLINE,
+                    // A grammar declaring its own tokens may leave delimiters out.
+                    _ => match self.KeywordLookup.get(&literal) {
+                        Some(v) => v.to_owned(),
+                        None => TokenKind::Operator
+                    }
+                }
             }
             BasicTokenKind::Int(format) => TokenKind::Int(format),
             BasicTokenKind::Float => TokenKind::Float,
             BasicTokenKind::String => TokenKind::String,
             BasicTokenKind::Char => TokenKind::Char,
-            BasicTokenKind::Comment => return self.Scan()
+            BasicTokenKind::Comment => return self.Next(previous)
         };
 
         match kind {
             TokenKind::NEWLINE => {
                 if self.CompleteSemicolon {
                     self.CompleteSemicolon = false;
-                    self.Token = Token {
+                    ok!(Token {
                         Pos: bt.Pos,
                         Kind: TokenKind::SEMICOLON,
                         Literal: ";".to_string(),
-                    };
-                    ok!(&self.Token);
+                        Number: None,
+                    });
                 }
-                return self.Scan();
-            }
-            TokenKind::Ident | TokenKind::Int(_) | TokenKind::RBRACE | TokenKind::RPAREN => {
-                self.CompleteSemicolon = true;
+                return self.Next(previous);
             }
             _ => {
-                self.CompleteSemicolon = false;
+                self.CompleteSemicolon = self.CompletesSemicolon(&kind);
             }
         }
 
-        self.Token = Token {
+        Ok(Token {
             Pos: bt.Pos,
             Kind: kind,
             Literal: bt.Literal.iter().collect(),
-        };
+            Number: bt.Number,
+        })
+    }
 
-        Ok(&self.Token)
+    // Parses with parse, reporting an unexpected token where it starts as wanting what is named.
+    pub fn Expecting<T>(&mut self, name: &str, parse: impl FnOnce(&mut Parser) -> Result<T, ParserError>) -> Result<T, ParserError> {
+        let begin = self.Token.Pos.Begin.Offset;
+        parse(self).map_err(|e| match e {
+            ParserError::UnexpectedToken(UnexpectedTokenError { Have, .. }) |
+            ParserError::Expected(ExpectedError { Have, .. }) if Have.Pos.Begin.Offset == begin => {
+                ParserError::Expected(ExpectedError { Name: name.to_string(), Have })
+            }
+            e => e
+        })
+    }
+
+    // Skips tokens up to one of the kinds or the end of input, e.g. to recover from an error.
+    pub fn SkipTo(&mut self, kinds: &[TokenKind]) -> Result<(), ParserError> {
+        while !kinds.iter().any(|kind| self.Token.Kind.Is(kind)) && !matches!(self.Token.Kind, TokenKind::EOF) {
+            self.Scan()?;
+        }
+        Ok(())
+    }
+
+    // MatchAndScan, also taking the literal of term off the start of an operator,
+    // e.g. the first `>` of `>>` closing nested angle brackets.
+    pub fn MatchPrefixAndScan(&mut self, term: TokenKind, literal: &str) -> Result<(), ParserError> {
+        let rest = match self.Token.Kind {
+            TokenKind::Operator => self.Token.Literal.strip_prefix(literal).map(str::to_string),
+            _ => None
+        };
+        match rest {
+            Some(rest) if !rest.is_empty() => {
+                let n = literal.chars().count();
+                self.Token.Pos.Begin.Offset += n;
+                self.Token.Pos.Begin.Column += n;
+                self.End = self.Token.Pos.Begin;
+                self.Token.Kind = self.KeywordLookup.get(&rest).cloned().unwrap_or(TokenKind::Operator);
+                self.Token.Literal = rest;
+                if self.Ahead.is_empty() {
+                    // The rest may end a line the whole operator could not.
+                    self.CompleteSemicolon = self.CompletesSemicolon(&self.Token.Kind);
+                }
+                Ok(())
+            }
+            _ => {
+                self.MatchAndScan(term)?;
+                Ok(())
+            }
+        }
     }
 
     pub fn GetTokenAndScan(&mut self) -> Result<Token, ParserError> {
@@ -126,7 +281,7 @@ impl Parser {
 
     pub fn Match(&mut self, term: TokenKind) -> Result<(), ParserError> {
         let tok = &self.Token;
-        if !tag_matches!(&tok.Kind, &term) {
+        if !tok.Kind.Is(&term) {
             unexpected_token!(term, tok.clone());
         }
         Ok(())
@@ -136,4 +291,9 @@ impl Parser {
         self.Match(term)?;
         Ok(self.Scan()?)
     }
+
+    // Whether a newline after a token of the kind completes a semicolon.
+    fn CompletesSemicolon(&self, kind: &TokenKind) -> bool {
+        self.SemicolonAfter.iter().any(|after| after.Is(kind))
+    }
 }
@@ -2,6 +2,7 @@
 // This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
 // that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
 
+use crate::ast::TokenKind;
 use crate::parser::parser::{Parser, ParserError};
 
 #[test]
@@ -24,3 +25,80 @@ fn TestParser_SemicolonComplete() {
         }
     }
 }
+
+#[test]
+fn TestParser_Peek() {
+    let mut p = Parser::new("a: (b)\nc\n".chars().collect());
+    p.Scan().unwrap();
+    let end = p.GetPos();
+
+    assert_eq!(p.Peek(2).unwrap().Literal, "(");
+    assert_eq!(p.Peek(0).unwrap().Literal, "a");
+    assert_eq!(p.Peek(1).unwrap().Literal, ":");
+    // Peeking does not move the parser.
+    assert_eq!(p.GetPos().Offset, end.Offset);
+
+    let literals: Vec<String> = (0..8).map(|n| p.Peek(n).unwrap().Literal.clone()).collect();
+    assert_eq!(literals[..7], ["a", ":", "(", "b", ")", ";", "c"]);
+
+    p.Scan().unwrap();
+    p.Scan().unwrap();
+    assert_eq!(p.Token.Literal, "(");
+    assert_eq!(p.GetPos().Offset, 4);
+}
+
+#[test]
+fn TestParser_MatchPrefixAndScan() {
+    let mut p = Parser::new("a<b<c>>\nd\n".chars().collect());
+    p.SemicolonAfter.push(TokenKind::GTR);
+    p.Scan().unwrap();
+
+    let mut literals: Vec<String> = vec![];
+    while !matches!(p.Token.Kind, TokenKind::EOF) {
+        literals.push(p.Token.Literal.clone());
+        match p.Token.Kind {
+            TokenKind::Operator => p.MatchPrefixAndScan(TokenKind::GTR, ">").unwrap(),
+            _ => { p.Scan().unwrap(); }
+        }
+    }
+    // The semicolon is completed after the second `>` of `>>`.
+    assert_eq!(literals, ["a", "<", "b", "<", "c", ">>", ">", ";", "d", ";"]);
+}
+
+#[test]
+fn TestParser_SemicolonAfter() {
+    fn literals(p: &mut Parser) -> Vec<String> {
+        let mut literals: Vec<String> = vec![];
+        while !matches!(p.Scan().unwrap().Kind, TokenKind::EOF) {
+            literals.push(p.Token.Literal.clone());
+        }
+        literals
+    }
+
+    let src = "a[1]\n\"b\"\nc\n";
+
+    let mut p = Parser::new(src.chars().collect());
+    assert_eq!(literals(&mut p), ["a", "[", "1", "]", "b", "c", ";"]);
+
+    let mut p = Parser::new(src.chars().collect());
+    p.SemicolonAfter.extend([TokenKind::RBRACK, TokenKind::String]);
+    assert_eq!(literals(&mut p), ["a", "[", "1", "]", ";", "b", ";", "c", ";"]);
+
+    // Lines may end in a float as in an int.
+    let mut p = Parser::new("x := 1.5
+y := 2
+".chars().collect());
+    assert_eq!(literals(&mut p), ["x", ":=", "1.5", ";", "y", ":=", "2", ";"]);
+}
+
+#[test]
+fn TestParser_SemicolonAtEnd() {
+    let mut p = Parser::new("a\n{b}".chars().collect());
+
+    let mut literals: Vec<String> = vec![];
+    while !matches!(p.Scan().unwrap().Kind, TokenKind::EOF) {
+        literals.push(p.Token.Literal.clone());
+    }
+    // The end of input ends the last line as a newline would.
+    assert_eq!(literals, ["a", ";", "{", "b", "}", ";"]);
+}
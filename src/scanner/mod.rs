@@ -2,12 +2,17 @@
 // This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
 // that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
 
+pub use number::*;
 pub use pos::*;
 pub use scanner::*;
 pub use token::*;
 
+pub mod number;
+
 pub mod pos;
 
 pub mod scanner;
 
 pub mod token;
+
+mod scanner_test;
@@ -0,0 +1,162 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use crate::scanner::*;
+
+// Unsigned integer of any width, the value of an int literal.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BigInt {
+    // Little-endian digits in base 2^32 without trailing zeros, empty for zero.
+    pub Digits: Vec<u32>,
+}
+
+impl BigInt {
+    // Parses digits of the radix, None if there are none or one is not of the radix.
+    pub fn Parse(digits: &[char], radix: u32) -> Option<BigInt> {
+        if digits.is_empty() {
+            return None;
+        }
+
+        let mut n = BigInt::default();
+        for ch in digits {
+            n.MulAdd(radix, ch.to_digit(radix)?);
+        }
+        Some(n)
+    }
+
+    fn MulAdd(&mut self, m: u32, a: u32) {
+        let mut carry = a as u64;
+        for digit in self.Digits.iter_mut() {
+            let v = *digit as u64 * m as u64 + carry;
+            *digit = v as u32;
+            carry = v >> 32;
+        }
+        if carry != 0 {
+            self.Digits.push(carry as u32);
+        }
+    }
+
+    // Number of bits up to the highest set one, 0 for zero.
+    pub fn Bits(&self) -> usize {
+        match self.Digits.last() {
+            Some(last) => self.Digits.len() * 32 - last.leading_zeros() as usize,
+            None => 0
+        }
+    }
+
+    pub fn ToU128(&self) -> Option<u128> {
+        if self.Bits() > 128 {
+            return None;
+        }
+        Some(self.Digits.iter().rev().fold(0, |n, &digit| n << 32 | digit as u128))
+    }
+
+    // The nearest f64, infinite if the int is wider than any.
+    pub fn ToF64(&self) -> f64 {
+        self.Digits.iter().rev().fold(0.0, |f, &digit| f * 4294967296.0 + digit as f64)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NumberValue {
+    Int(BigInt),
+    Float(f64),
+}
+
+// Decoded value of an Int or Float token.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Number {
+    pub Value: NumberValue,
+    // Type suffix, e.g. u8 of 10u8, empty if there is none.
+    pub Suffix: String,
+}
+
+impl Number {
+    // Decodes the literal of a number token without its suffix, None if it has no digits.
+    //
+    // Underscores separate digits, ints leave out their prefix while a hex float keeps its `0x`, see ScanDigit.
+    pub fn Decode(kind: &BasicTokenKind, literal: &[char], suffix: String) -> Option<Number> {
+        let digits: Vec<char> = literal.iter().copied().filter(|&ch| ch != '_').collect();
+
+        let value = match kind {
+            BasicTokenKind::Int(format) => NumberValue::Int(BigInt::Parse(&digits, *format as u32)?),
+            BasicTokenKind::Float => match digits.strip_prefix(&['0', 'x']) {
+                Some(digits) => NumberValue::Float(DecodeHexFloat(digits)?),
+                None => NumberValue::Float(String::from_iter(digits).parse().ok()?),
+            },
+            _ => return None
+        };
+
+        Some(Number {
+            Value: value,
+            Suffix: suffix,
+        })
+    }
+
+    // Whether the value is in range of the type of its suffix.
+    //
+    // Signed ints may take the magnitude of their minimum, as the literal may be negated.
+    // Ints without a suffix are of any width, floats without one are f64.
+    pub fn Fits(&self) -> bool {
+        match &self.Value {
+            NumberValue::Int(n) => match (IntBits(&self.Suffix), self.Suffix.as_str()) {
+                (Some((bits, false)), _) => n.Bits() <= bits,
+                (Some((bits, true)), _) => n.ToU128().is_some_and(|n| n <= 1 << (bits - 1)),
+                (None, "") => true,
+                (None, suffix) => FloatFits(n.ToF64(), suffix),
+            },
+            NumberValue::Float(f) => FloatFits(*f, &self.Suffix),
+        }
+    }
+}
+
+// Whether a suffix names a number type, see BasicScanner.ScanSuffix.
+pub fn IsSuffix(suffix: &str) -> bool {
+    IntBits(suffix).is_some() || matches!(suffix, "f32" | "f64")
+}
+
+// Width and signedness of an int type suffix.
+fn IntBits(suffix: &str) -> Option<(usize, bool)> {
+    Some(match suffix {
+        "u8" => (8, false),
+        "u16" => (16, false),
+        "u32" => (32, false),
+        "u64" | "usize" => (64, false),
+        "u128" => (128, false),
+        "i8" => (8, true),
+        "i16" => (16, true),
+        "i32" => (32, true),
+        "i64" | "isize" => (64, true),
+        "i128" => (128, true),
+        _ => return None
+    })
+}
+
+// Whether the float is in range of the float type of the suffix, int types never fit.
+fn FloatFits(f: f64, suffix: &str) -> bool {
+    match suffix {
+        "f32" => f.abs() <= f32::MAX as f64,
+        "" | "f64" => f.is_finite(),
+        _ => false
+    }
+}
+
+// Decodes a hex float after its `0x` prefix, e.g. 1.8p3.
+fn DecodeHexFloat(digits: &[char]) -> Option<f64> {
+    let p = digits.iter().position(|&ch| ch == 'p' || ch == 'P')?;
+    let exponent: i32 = String::from_iter(&digits[p + 1..]).parse().ok()?;
+
+    let mut mantissa = 0.0;
+    let mut fraction = None;
+    for &ch in &digits[..p] {
+        match ch {
+            '.' => fraction = Some(0),
+            _ => {
+                mantissa = mantissa * 16.0 + ch.to_digit(16)? as f64;
+                fraction = fraction.map(|n| n + 4);
+            }
+        }
+    }
+    Some(mantissa * 2.0f64.powi(exponent.saturating_sub(fraction.unwrap_or(0))))
+}
@@ -4,6 +4,8 @@
 
 #[derive(Copy, Clone, Default)]
 pub struct Position {
+    // Index of the file among the files loaded together, see build::Loader.
+    pub Source: usize,
     pub Offset: usize,
     pub Line: usize,
     pub Column: usize,
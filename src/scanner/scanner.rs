@@ -3,7 +3,7 @@
 // that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
 
 use std::char::from_u32;
-use std::fmt::{Debug, Formatter};
+use std::fmt::{Debug, Display, Formatter};
 
 use err_rs::*;
 
@@ -30,12 +30,16 @@ pub struct EOFError {
     pub Pos: Position,
 }
 
-impl Debug for EOFError {
+impl Display for EOFError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: end of file", self.Pos)
+        write!(f, "end of file")
     }
 }
 
+impl Debug for EOFError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{}: {}", self.Pos, self) }
+}
+
 impl BufferScanner {
     pub fn GetChar(&self) -> Result<char, EOFError> {
         if self.Pos.Offset == self.Buffer.len() {
@@ -47,6 +51,11 @@ impl BufferScanner {
         Ok(self.Buffer[self.Pos.Offset])
     }
 
+    // The char n chars after the current one without moving, PeekChar(0) is the current char.
+    pub fn PeekChar(&self, n: usize) -> Option<char> {
+        self.Buffer.get(self.Pos.Offset + n).copied()
+    }
+
     pub fn Move(&mut self) -> Result<char, EOFError> {
         let ch = self.GetChar()?;
 
@@ -81,29 +90,76 @@ impl BufferScanner {
 pub enum BasicScannerError {
     EOF(EOFError),
     BadFormat(BadFormatError),
+    BadChar(BadCharError),
 }
 
 pub struct BadFormatError {
     pub PosRange: PosRange,
 }
 
+impl Display for BadFormatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "format error")
+    }
+}
+
 impl Debug for BadFormatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{}: {}", self.PosRange, self) }
+}
+
+// A char literal without exactly one char.
+pub struct BadCharError {
+    pub PosRange: PosRange,
+    pub Chars: usize,
+}
+
+impl Display for BadCharError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}: format error", self.PosRange)
+        match self.Chars {
+            0 => write!(f, "empty char literal"),
+            n => write!(f, "char literal of {} chars", n),
+        }
+    }
+}
+
+impl Debug for BadCharError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{}: {}", self.PosRange, self) }
+}
+
+// A number literal out of range of its type, see Number.Fits and BasicScanner.Overflows.
+pub struct OverflowError {
+    pub PosRange: PosRange,
+    pub Type: String,
+}
+
+impl Display for OverflowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "literal out of range for {}", self.Type)
     }
 }
 
+impl Debug for OverflowError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result { write!(f, "{}: {}", self.PosRange, self) }
+}
+
 pub struct BasicScanner {
     pub BufferScanner: BufferScanner,
 
     pub Delimiters: Vec<char>,
     pub Whitespaces: Vec<char>,
+
+    // Scan single quotes as char literals rather than strings.
+    pub CharLiterals: bool,
+
+    // Number literals out of range of their type, scanned on as tokens without a Number.
+    pub Overflows: Vec<OverflowError>,
 }
 
 impl BufferScanner {
     pub fn new(buffer: Vec<char>) -> BufferScanner {
         BufferScanner {
             Pos: Position {
+                Source: 0,
                 Offset: 0,
                 Line: 0,
                 Column: 0,
@@ -139,6 +195,7 @@ impl BasicScanner {
             Pos: range![begin, self],
             Kind: BasicTokenKind::Comment,
             Literal: collect_from_to!(self, begin),
+            Number: None,
         })
     }
 
@@ -157,6 +214,7 @@ impl BasicScanner {
             Pos: range![begin, self],
             Kind: BasicTokenKind::Comment,
             Literal: collect_from_to!(self, begin),
+            Number: None,
         })
     }
 
@@ -177,122 +235,215 @@ impl BasicScanner {
     pub fn ScanIdent(&mut self) -> Result<BasicToken, BasicScannerError> {
         let begin = self.GetPos();
 
-        loop {
-            let ch = self.GetChar()?;
-            if ch.is_ascii_alphabetic() || ch.is_numeric() || ch == '_' {
-                self.Move()?;
-            } else {
-                break;
-            }
+        while self.BufferScanner.PeekChar(0).is_some_and(|ch| ch.is_ascii_alphabetic() || ch.is_numeric() || ch == '_') {
+            self.Move()?;
         }
 
         Ok(BasicToken {
             Pos: range![begin, self],
             Kind: BasicTokenKind::Ident,
             Literal: collect_from_to!(self, begin),
+            Number: None,
         })
     }
 
     pub fn ScanHex(&mut self) -> Result<BasicToken, BasicScannerError> {
         let begin = self.GetPos();
 
-        loop {
-            let ch = self.GetChar()?;
-            if '0' <= ch && ch <= '9' || 'a' <= ch && ch <= 'f' {
-                self.Move()?;
-            } else {
-                break;
-            }
+        while self.BufferScanner.PeekChar(0).is_some_and(|ch| ch.is_ascii_hexdigit() || ch == '_') {
+            self.Move()?;
         }
 
         Ok(BasicToken {
             Pos: range![begin, self],
             Kind: BasicTokenKind::Int(IntFormat::HEX),
             Literal: collect_from_to!(self, begin),
+            Number: None,
         })
     }
 
     pub fn ScanDec(&mut self) -> Result<BasicToken, BasicScannerError> {
         let begin = self.GetPos();
 
-        loop {
-            let ch = self.GetChar()?;
-            if '0' <= ch && ch <= '9' {
-                self.Move()?;
-            } else {
-                break;
+        self.ScanDecimals()?;
+
+        let mut kind = BasicTokenKind::Int(IntFormat::DEC);
+
+        if self.BufferScanner.PeekChar(0) == Some('.') && self.IsFraction() {
+            self.Move()?;
+            self.ScanDecimals()?;
+            kind = BasicTokenKind::Float;
+        }
+        if matches!(self.BufferScanner.PeekChar(0), Some('e' | 'E')) && self.IsExponent(0) {
+            self.ScanExponent()?;
+            kind = BasicTokenKind::Float;
+        }
+
+        Ok(BasicToken {
+            Pos: range![begin, self],
+            Kind: kind,
+            Literal: collect_from_to!(self, begin),
+            Number: None,
+        })
+    }
+
+    pub fn ScanDecimals(&mut self) -> Result<(), BasicScannerError> {
+        while self.BufferScanner.PeekChar(0).is_some_and(|ch| '0' <= ch && ch <= '9' || ch == '_') {
+            self.Move()?;
+        }
+
+        Ok(())
+    }
+
+    // Whether the dot at the current char starts a fraction, it needs a digit after the dot
+    // leaving `1..2` and `1.max()` to operators.
+    fn IsFraction(&self) -> bool {
+        matches!(self.BufferScanner.PeekChar(1), Some('0'..='9'))
+    }
+
+    // Whether the exponent mark n chars after the current one is followed by an optionally signed decimal.
+    fn IsExponent(&self, n: usize) -> bool {
+        match self.BufferScanner.PeekChar(n + 1) {
+            Some('+' | '-') => matches!(self.BufferScanner.PeekChar(n + 2), Some('0'..='9')),
+            ch => matches!(ch, Some('0'..='9'))
+        }
+    }
+
+    // Scans an exponent from its mark, e.g. e-9 or p3, see IsExponent.
+    pub fn ScanExponent(&mut self) -> Result<(), BasicScannerError> {
+        self.Move()?;
+        if matches!(self.BufferScanner.PeekChar(0), Some('+' | '-')) {
+            self.Move()?;
+        }
+        self.ScanDecimals()
+    }
+
+    // Whether a hex float follows the `0x` prefix, its mantissa needs a `p` exponent
+    // to tell it from a hex int followed by a dot.
+    fn IsHexFloat(&self) -> bool {
+        let mut digits = false;
+        let mut dot = false;
+        for n in 0.. {
+            match self.BufferScanner.PeekChar(n) {
+                Some('0'..='9' | 'a'..='f' | 'A'..='F') => digits = true,
+                Some('_') => {}
+                Some('.') if !dot => dot = true,
+                Some('p' | 'P') => return digits && self.IsExponent(n),
+                _ => break
             }
         }
+        false
+    }
+
+    // Scans a hex float from its `0x` prefix at begin, e.g. 0x1.8p3, see IsHexFloat.
+    pub fn ScanHexFloat(&mut self, begin: Position) -> Result<BasicToken, BasicScannerError> {
+        while !matches!(self.GetChar()?, 'p' | 'P') {
+            self.Move()?;
+        }
+        self.ScanExponent()?;
 
         Ok(BasicToken {
             Pos: range![begin, self],
-            Kind: BasicTokenKind::Int(IntFormat::DEC),
+            Kind: BasicTokenKind::Float,
             Literal: collect_from_to!(self, begin),
+            Number: None,
         })
     }
 
     pub fn ScanOct(&mut self) -> Result<BasicToken, BasicScannerError> {
         let begin = self.GetPos();
 
-        loop {
-            let ch = self.GetChar()?;
-            if '0' <= ch && ch <= '7' {
-                self.Move()?;
-            } else {
-                break;
-            }
+        while self.BufferScanner.PeekChar(0).is_some_and(|ch| '0' <= ch && ch <= '7' || ch == '_') {
+            self.Move()?;
         }
 
         Ok(BasicToken {
             Pos: range![begin, self],
             Kind: BasicTokenKind::Int(IntFormat::OCT),
             Literal: collect_from_to!(self, begin),
+            Number: None,
         })
     }
 
     pub fn ScanBin(&mut self) -> Result<BasicToken, BasicScannerError> {
         let begin = self.GetPos();
 
-        loop {
-            let ch = self.GetChar()?;
-            if ch == '0' || ch == '1' {
-                self.Move()?;
-            } else {
-                break;
-            }
+        while self.BufferScanner.PeekChar(0).is_some_and(|ch| ch == '0' || ch == '1' || ch == '_') {
+            self.Move()?;
         }
 
         Ok(BasicToken {
             Pos: range![begin, self],
             Kind: BasicTokenKind::Int(IntFormat::BIN),
             Literal: collect_from_to!(self, begin),
+            Number: None,
         })
     }
 
     pub fn ScanDigit(&mut self) -> Result<BasicToken, BasicScannerError> {
         let begin = self.GetPos();
 
-        match self.Move()? {
-            '0' => {
-                match self.Move()? {
-                    'x' => self.ScanHex(),
-                    'o' => self.ScanOct(),
-                    'b' => self.ScanBin(),
-                    _ => {
-                        err!(BasicScannerError::BadFormat, BadFormatError {
-                            PosRange: PosRange {
-                                Begin: begin,
-                                End: self.GetPos(),
-                            },
-                        })
-                    }
+        let token = match (self.GetChar()?, self.BufferScanner.PeekChar(1)) {
+            ('0', Some(prefix @ ('x' | 'o' | 'b'))) => {
+                self.Move()?;
+                self.Move()?;
+                let token = match prefix {
+                    'x' if self.IsHexFloat() => self.ScanHexFloat(begin)?,
+                    'x' => self.ScanHex()?,
+                    'o' => self.ScanOct()?,
+                    _ => self.ScanBin()?,
+                };
+                // The literal of an int is its digits without the prefix, its range takes it in.
+                BasicToken {
+                    Pos: range![begin, self],
+                    ..token
                 }
             }
-            _ => {
-                self.BufferScanner.Pos = begin;
-                self.ScanDec()
-            }
+            _ => self.ScanDec()?
+        };
+
+        self.ScanSuffix(token)
+    }
+
+    // Scans the type suffix after the digits of a number token, e.g. u8 of 10u8, and decodes its value.
+    //
+    // Only the name of a number type is a suffix, other letters after the digits are left to an ident, e.g. px of 10px.
+    pub fn ScanSuffix(&mut self, token: BasicToken) -> Result<BasicToken, BasicScannerError> {
+        let name: Vec<char> = (0..).map_while(|n| self.BufferScanner.PeekChar(n))
+            .take_while(|&ch| ch.is_ascii_alphanumeric() || ch == '_')
+            .collect();
+        let suffix = match IsSuffix(&String::from_iter(&name)) {
+            true => name,
+            false => vec![],
+        };
+        for _ in &suffix {
+            self.Move()?;
         }
+
+        let pos = PosRange { Begin: token.Pos.Begin, End: self.GetPos() };
+
+        let number = match Number::Decode(&token.Kind, &token.Literal, String::from_iter(&suffix)) {
+            Some(number) => number,
+            None => err!(BasicScannerError::BadFormat, BadFormatError { PosRange: pos })
+        };
+        let number = match number.Fits() {
+            true => Some(number),
+            false => {
+                let typ = match (&number.Value, number.Suffix.as_str()) {
+                    (NumberValue::Float(_), "") => "f64".to_string(),
+                    (_, suffix) => suffix.to_string(),
+                };
+                self.Overflows.push(OverflowError { PosRange: pos, Type: typ });
+                None
+            }
+        };
+
+        Ok(BasicToken {
+            Pos: pos,
+            Kind: token.Kind,
+            Literal: [token.Literal, suffix].concat(),
+            Number: number,
+        })
     }
 
     pub fn ScanUnicodeHex(&mut self, runesN: u8) -> Result<char, BasicScannerError> {
@@ -365,14 +516,32 @@ impl BasicScanner {
             Pos: range![begin, self],
             Kind: BasicTokenKind::String,
             Literal: seq,
+            Number: None,
+        })
+    }
+
+    // Scans a char literal of exactly one, possibly escaped, char.
+    pub fn ScanChar(&mut self) -> Result<BasicToken, BasicScannerError> {
+        let token = self.ScanString('\'')?;
+
+        if token.Literal.len() != 1 {
+            err!(BasicScannerError::BadChar, BadCharError {
+                PosRange: token.Pos,
+                Chars: token.Literal.len(),
+            })
+        }
+
+        Ok(BasicToken {
+            Kind: BasicTokenKind::Char,
+            ..token
         })
     }
 
     pub fn ScanOperator(&mut self) -> Result<BasicToken, BasicScannerError> {
         let begin = self.GetPos();
 
-        loop {
-            match self.GetChar()? {
+        while let Some(ch) = self.BufferScanner.PeekChar(0) {
+            match ch {
                 '"' => break,
                 '\'' => break,
                 ch if !ch.is_ascii_punctuation() => break,
@@ -385,6 +554,7 @@ impl BasicScanner {
             Pos: range![begin, self],
             Kind: BasicTokenKind::Operator,
             Literal: collect_from_to!(self, begin),
+            Number: None,
         })
     }
 
@@ -396,15 +566,18 @@ impl BasicScanner {
         match self.GetChar()? {
             ch if ch.is_alphabetic() => self.ScanIdent(),
             ch if ch.is_numeric() => self.ScanDigit(),
-            ch if self.Delimiters.contains(&ch) => Ok(
-                BasicToken {
+            ch if self.Delimiters.contains(&ch) => {
+                self.Move()?;
+                Ok(BasicToken {
                     Pos: range![begin, self],
                     Kind: BasicTokenKind::Delimiter,
-                    Literal: vec![self.Move()?],
-                }
-            ),
+                    Literal: vec![ch],
+                    Number: None,
+                })
+            }
             '_' => self.ScanIdent(),
             '"' => self.ScanString('"'),
+            '\'' if self.CharLiterals => self.ScanChar(),
             '\'' => self.ScanString('\''),
             '/' => self.ScanComment(),
             ch if ch.is_ascii_punctuation() => self.ScanOperator(),
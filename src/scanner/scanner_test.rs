@@ -0,0 +1,140 @@
+// Copyright 2024 Jelly Terra
+// This Source Code Form is subject to the terms of the Mozilla Public License, v. 2.0
+// that can be found in the LICENSE file and https://mozilla.org/MPL/2.0/.
+
+use crate::scanner::*;
+
+fn new(src: &str) -> BasicScanner {
+    BasicScanner {
+        BufferScanner: BufferScanner::new(src.chars().collect()),
+        Delimiters: vec!['(', ')', '\n'],
+        Whitespaces: vec![' '],
+        CharLiterals: false,
+        Overflows: vec![],
+    }
+}
+
+fn scan(mut s: BasicScanner) -> Vec<BasicToken> {
+    let mut tokens = vec![];
+    loop {
+        match s.Scan() {
+            Ok(token) => tokens.push(token),
+            Err(BasicScannerError::EOF(_)) => return tokens,
+            Err(e) => panic!("{:?}", e)
+        }
+    }
+}
+
+fn literals(tokens: &[BasicToken], filter: fn(&BasicTokenKind) -> bool) -> Vec<String> {
+    tokens.iter().filter(|t| filter(&t.Kind)).map(|t| t.Literal.iter().collect()).collect()
+}
+
+#[test]
+fn TestBasicScanner_Float() {
+    let tokens = scan(new("1.5 1e-9 2.5E+3 0.5 0e3 0x1.8p3 0x1p-2 a.b 1..2 1.max() 0xf.a\n"));
+
+    assert_eq!(literals(&tokens, |k| matches!(k, BasicTokenKind::Float)), ["1.5", "1e-9", "2.5E+3", "0.5", "0e3", "0x1.8p3", "0x1p-2"]);
+    // Dots without a fraction after them are left to operators.
+    assert_eq!(literals(&tokens, |k| matches!(k, BasicTokenKind::Int(_))), ["1", "2", "1", "f"]);
+    assert_eq!(literals(&tokens, |k| matches!(k, BasicTokenKind::Operator)), [".", "..", ".", "."]);
+    assert_eq!(literals(&tokens, |k| matches!(k, BasicTokenKind::Ident)), ["a", "b", "max", "a"]);
+
+    let float = &tokens[0];
+    assert_eq!((float.Pos.Begin.Offset, float.Pos.End.Offset), (0, 3));
+    let hex = &tokens[tokens.len() - 4];
+    assert_eq!((hex.Pos.Begin.Offset, hex.Pos.End.Offset), (56, 59));
+
+    // An exponent mark without digits is left to an ident.
+    let tokens = scan(new("1e 1.5E+ 0x1p\n"));
+    assert_eq!(literals(&tokens, |k| matches!(k, BasicTokenKind::Ident)), ["e", "E", "p"]);
+    assert_eq!(literals(&tokens, |k| !matches!(k, BasicTokenKind::Ident | BasicTokenKind::Delimiter)), ["1", "1.5", "+", "1"]);
+}
+
+#[test]
+fn TestBasicScanner_Delimiter() {
+    let tokens = scan(new("(a)\n"));
+
+    let ranges: Vec<(usize, usize)> = tokens.iter()
+        .filter(|t| matches!(t.Kind, BasicTokenKind::Delimiter))
+        .map(|t| (t.Pos.Begin.Offset, t.Pos.End.Offset))
+        .collect();
+    // Each delimiter spans its own char.
+    assert_eq!(ranges, [(0, 1), (2, 3), (3, 4)]);
+}
+
+#[test]
+fn TestBasicScanner_Char() {
+    let src = "'a' '\\n' '\\'' 'bc'\n";
+
+    // Strings by default.
+    let tokens = scan(new(src));
+    assert_eq!(literals(&tokens, |k| matches!(k, BasicTokenKind::String)), ["a", "\n", "'", "bc"]);
+
+    let mut s = new(src);
+    s.CharLiterals = true;
+    let mut chars = vec![];
+    loop {
+        match s.Scan() {
+            Ok(token) => chars.push(token),
+            Err(BasicScannerError::BadChar(e)) => {
+                assert_eq!((e.PosRange.Begin.Offset, e.Chars), (14, 2));
+                break;
+            }
+            Err(e) => panic!("{:?}", e)
+        }
+    }
+    assert_eq!(literals(&chars, |k| matches!(k, BasicTokenKind::Char)), ["a", "\n", "'"]);
+
+    let mut s = new("''\n");
+    s.CharLiterals = true;
+    assert!(matches!(s.Scan(), Err(BasicScannerError::BadChar(BadCharError { Chars: 0, .. }))));
+}
+
+#[test]
+fn TestBasicScanner_Number() {
+    let tokens = scan(new("0 1_000 0xFF_ff 0o17 0b1010 10u8 1.0f32 1_0.2_5e1_0 0x1.8p3 340282366920938463463374607431768211456 -128i8\n"));
+
+    let values: Vec<&Number> = tokens.iter().filter_map(|t| t.Number.as_ref()).collect();
+    let ints: Vec<Option<u128>> = values.iter().map(|n| match &n.Value {
+        NumberValue::Int(n) => n.ToU128(),
+        NumberValue::Float(_) => None,
+    }).collect();
+    assert_eq!(ints, [Some(0), Some(1000), Some(0xffff), Some(0o17), Some(0b1010), Some(10), None, None, None, None, Some(128)]);
+    assert_eq!(values[6].Value, NumberValue::Float(1.0));
+    assert_eq!(values[7].Value, NumberValue::Float(10.25e10));
+    assert_eq!(values[8].Value, NumberValue::Float(12.0));
+    // 2^128 is wider than any int type.
+    assert!(matches!(&values[9].Value, NumberValue::Int(n) if n.Bits() == 129));
+
+    assert_eq!((values[5].Suffix.as_str(), values[6].Suffix.as_str()), ("u8", "f32"));
+    // Ints leave their prefix out of the literal but not out of the range.
+    let ints = literals(&tokens, |k| matches!(k, BasicTokenKind::Int(_)));
+    assert_eq!((ints[2].as_str(), ints[3].as_str(), ints[5].as_str()), ("FF_ff", "17", "10u8"));
+    assert_eq!((tokens[2].Pos.Begin.Offset, tokens[2].Pos.End.Offset), (8, 15));
+
+    // Letters not naming a number type are not a suffix.
+    let tokens = scan(new("10px 3x 1u8x 2_u16\n"));
+    assert_eq!(literals(&tokens, |k| !matches!(k, BasicTokenKind::Delimiter)), ["10", "px", "3", "x", "1", "u8x", "2_u16"]);
+
+    // Scanning goes on past literals out of range.
+    let mut s = new("256u8 129i8 1e39f32 1e309 1.5u8 1\n");
+    let mut tokens = vec![];
+    while let Ok(token) = s.Scan() {
+        tokens.push(token);
+    }
+    assert_eq!(tokens.iter().map(|t| t.Number.is_some()).collect::<Vec<bool>>(), [false, false, false, false, false, true, false]);
+    // An int suffix does not fit a float.
+    assert_eq!(s.Overflows.iter().map(|e| e.Type.as_str()).collect::<Vec<&str>>(), ["u8", "i8", "f32", "f64", "u8"]);
+    assert_eq!((s.Overflows[1].PosRange.Begin.Offset, s.Overflows[1].PosRange.End.Offset), (6, 11));
+    assert!(matches!(new("0x\n").Scan(), Err(BasicScannerError::BadFormat(_))));
+}
+
+#[test]
+fn TestBasicScanner_EOF() {
+    // A token ending the input is scanned like any other.
+    for src in ["a", "a_1", "12", "1_000", "1.5", "1e3", "0x1.8p3", "0x1f", "0o7", "0b1", "10u8", "+="] {
+        let tokens = scan(new(src));
+        assert_eq!(tokens.len(), 1, "{}", src);
+        assert_eq!((tokens[0].Pos.Begin.Offset, tokens[0].Pos.End.Offset), (0, src.len()), "{}", src);
+    }
+}
@@ -33,4 +33,6 @@ pub struct BasicToken {
     pub Pos: PosRange,
     pub Kind: BasicTokenKind,
     pub Literal: Vec<char>,
+    // Decoded value of Int and Float tokens.
+    pub Number: Option<Number>,
 }